@@ -1,7 +1,13 @@
 export type { GenerateOptions, TextGenerator } from './generate/pipeline';
 export { createGenerator } from './generate/pipeline';
 export { loadManifest } from './manifest/loader';
-export type { ModelManifest, Shard, ShardKind } from './manifest/types';
+export type {
+	DownloadHints,
+	LayerDigest,
+	ModelManifest,
+	Shard,
+	ShardKind,
+} from './manifest/types';
 
 export type { SessionOptions } from './session/manager';
 export { createSession, destroySession } from './session/manager';
@@ -11,6 +11,28 @@ export interface Shard {
 	hash: string;
 	/** 仅 kind=layer 时存在，[start, end] 含两端 */
 	layer_range?: [number, number];
+	/** 仅 kind=layer 时存在：依赖的 embed 分片与全部更低层 layer 分片的 id */
+	depends_on?: string[];
+	/** 仅跨多层的 layer 分片：层号 → 该层字节区间及 hash，用于定位损坏的层 */
+	layer_digests?: Record<string, LayerDigest>;
+	/** 自定义键值对，如训练步数、数据版本 */
+	metadata?: Record<string, string>;
+}
+
+export interface LayerDigest {
+	/** 区间在分片文件中的起始字节 */
+	offset: number;
+	/** 区间字节数 */
+	length: number;
+	/** 格式: "blake3:<hex>" */
+	hash: string;
+}
+
+export interface DownloadHints {
+	/** 建议并发下载数 */
+	suggested_parallelism: number;
+	/** 全部分片字节数之和 */
+	total_bytes: number;
 }
 
 export interface ModelManifest {
@@ -21,4 +43,22 @@ export interface ModelManifest {
 	dtype: string;
 	total_layers: number;
 	shards: Shard[];
+	/** 整套分片的总 hash，格式 "blake3:<hex>"，算法见 specs/manifest.schema-notes.md */
+	manifest_digest?: string;
+	/** 旧 manifest 可能没有此字段 */
+	download_hints?: DownloadHints;
+	/** lm_head 与 embed 共享权重时为 true，此时没有 lm_head 分片 */
+	tie_word_embeddings?: boolean;
+	/** 仅写出了部分分片时为 true，不可用于推理 */
+	partial?: boolean;
+	/** 仅 partial 时存在：已写出分片覆盖的层号 (升序) */
+	included_layers?: number[];
+	/** 只写出了部分 base 分组时存在：分片中包含的 base 分组 ("embed_tokens" | "norm" | "lm_head") */
+	included_base?: string[];
+	/** 分片内 tensor 顺序的打乱种子，不超过 Number.MAX_SAFE_INTEGER */
+	shuffle_seed?: number;
+	/** 发布者信息 */
+	created_by?: string;
+	/** 自由文本备注，如发布说明 */
+	notes?: string;
 }
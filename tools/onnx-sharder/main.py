@@ -9,33 +9,245 @@
       --dtype int8 \
       --layers-per-chunk 1 \
       --copy-tokenizer ../../models/tinyllama-1.1b-chat-int8/
+
+    # 按 manifest.json 并发校验已下载 / 已生成的分片
+    python main.py --verify --output ../../dist/tinyllama-int8/ --jobs 8
+
+    # 只提取指定的 tensor 到 extract.onnx + extract.onnx_data
+    python main.py --input model.onnx --output ./debug/ --extract model.norm.weight,onnx::MatMul_1234
+
+    # 与已知正确的参考输出逐分片比较 (回归检查)
+    python main.py --compare ../../dist/tinyllama-int8-ref/ --output ../../dist/tinyllama-int8/
+
+    # 列出本地目录中仍需下载的分片 (断点续传)
+    python main.py --plan-download manifest.json --output ./local-cache/ --plan-json
+
+    # 只查看模型概况，不写文件
+    python main.py --probe --input ../../models/tinyllama-1.1b-chat-int8/model_quantized.onnx
+
+    # CI 中只检查已发布 manifest 的一致性，不需要分片文件
+    python main.py --validate-only ../../dist/tinyllama-int8/manifest.json
+
+    # 内置微型模型端到端自检
+    python main.py --self-test
+
+    # 分片已存在、仅重新生成 manifest.json
+    python main.py --manifest-only \
+      --output ../../dist/tinyllama-int8/ \
+      --model-id "TinyLlama/TinyLlama-1.1B-Chat-v1.0" \
+      --variant base \
+      --dtype int8
+
+退出码:
+    0 成功 / 1 内部错误 / 2 参数错误 / 3 输入错误
+    4 校验失败 / 5 输出 I/O 错误 / 6 完整性不匹配 / 130 被取消
+    (详见 src/errors.py)
+
+    --json-errors 时失败信息为 stderr 上的单个 JSON 对象:
+    {"error": <category>, "message": <str>, "context": [<原因链>]}
+    命令行参数错误 (如缺少 --input) 时 "error" 为 "usage"，退出码仍为 2
 """
 
+import contextlib
+import json
+import os
+import sys
+
+from src import progress
 from src.cli import parse_args
-from src.parser import load_onnx_model, classify_initializers, print_summary
-from src.writer import write_shards, ModelManifest, generate_config, copy_tokenizer
+from src.errors import Cancelled, IntegrityError, OutputError, SharderError, ValidationError
+from src.parser import (
+    load_onnx_model, classify_initializers, dtype_histogram, missing_base_tensors, print_summary,
+    unused_base_tensors,
+)
+from src.probe import run_probe
+from src.selftest import run_self_test
+from src.sources import verify_sources
+from src.validate import run_validate
+from src.verify import plan_download, run_compare, run_verify
+from src.writer import (
+    plan_shards, write_shards, rebuild_shards, embed_manifest, ModelManifest, covered_layers,
+    oversized_base_shards, generate_config, copy_tokenizer, store_content_addressed,
+    assign_layer_digests, extract_tensors, write_chunks_readme, clean_temp_files, padding_bytes,
+    plan_excluded_base, plan_excluded_layers,
+)
 
 
-def main():
-    args = parse_args()
+def check_layer_index(result, max_layer_index: int) -> None:
+    """层号超过 --max-layer-index 时报错，避免按异常的层号规划出海量分片。"""
+    if result.max_layer > max_layer_index:
+        offenders = [t.name for t in result.layers[result.max_layer]][:5]
+        raise ValidationError(
+            f"层索引异常大，疑似解析错误: layer {result.max_layer} ({', '.join(offenders)})，"
+            f"超过 --max-layer-index {max_layer_index}"
+        )
+
+
+def regenerate_manifest(args) -> None:
+    """--manifest-only: 根据输出目录中已有的分片重新生成 manifest.json。"""
+    model_path = args.output / "model.onnx"
+    print(f"\n[1/3] 加载图定义: {model_path}")
+    model = load_onnx_model(model_path, load_external_data=False)
+
+    print("\n[2/3] 分类 initializers...")
+    result = classify_initializers(list(model.graph.initializer), model.graph)
+    check_layer_index(result, args.max_layer_index)
+
+    print("\n[3/3] 重新计算分片 hash...")
+    shards, partial = rebuild_shards(model, result, args.output, args.base_include)
+    for shard in shards:
+        shard.metadata = dict(args.chunk_meta) or None
+    manifest = ModelManifest(
+        model_id=args.model_id,
+        variant=args.variant,
+        framework="onnxruntime-web",
+        dtype=args.dtype,
+        total_layers=result.max_layer + 1 if result.max_layer >= 0 else 0,
+        shards=shards,
+        tie_word_embeddings=result.tied_lm_head,
+        partial=partial,
+        included_layers=covered_layers(shards) if partial else None,
+        included_base=args.base_include,
+        shuffle_seed=args.shuffle_seed,
+        created_by=args.created_by,
+        notes=args.notes,
+    )
+    manifest.write(
+        args.output, args.manifest_out, compact=args.compact_manifest, fsync=args.fsync,
+    )
+    if args.emit_readme:
+        write_chunks_readme(manifest, args.output)
+
+
+def main(args):
+    if args.progress_json:
+        progress.enable()
+
+    if args.self_test:
+        if not run_self_test():
+            raise IntegrityError("自检未通过")
+        return
+
+    if args.probe:
+        run_probe(args.input)
+        return
+
+    if args.validate_only is not None:
+        run_validate(args.validate_only)
+        return
+
+    if args.verify:
+        if not run_verify(args.output, args.jobs, args.manifest_out):
+            raise IntegrityError("分片校验未通过")
+        return
+
+    if args.compare is not None:
+        if not run_compare(args.output, args.compare, args.jobs, args.manifest_out):
+            raise IntegrityError("与参考输出不一致")
+        return
+
+    if args.plan_download is not None:
+        plan_download(args.plan_download, args.output, args.jobs, as_json=args.plan_json)
+        return
+
+    if args.manifest_only:
+        regenerate_manifest(args)
+        return
+
+    if args.verify_sources is not None:
+        verify_sources(args.verify_sources)
+
+    if args.extract is not None:
+        print(f"\n加载模型: {args.input}")
+        model = load_onnx_model(args.input)
+        print(f"\n提取 {len(args.extract)} 个 tensor 到 {args.output}")
+        extract_tensors(model, args.extract, args.output)
+        return
 
     # Step 1: 加载 ONNX 模型
     print(f"\n[1/5] 加载模型: {args.input}")
     model = load_onnx_model(args.input)
+    progress.emit("load_done", initializers=len(model.graph.initializer))
 
     # Step 2: 分类 initializer
     print("\n[2/5] 分类 initializers...")
-    result = classify_initializers(list(model.graph.initializer), model.graph)
+    result = classify_initializers(
+        list(model.graph.initializer), model.graph, verbose=args.verbose_tensors,
+    )
     print_summary(result)
 
+    missing = missing_base_tensors(result)
+    if missing:
+        message = f"缺少必需的 base 权重: {', '.join(missing)} (可能被错误归类)"
+        if args.strict_base:
+            raise ValidationError(f"{message} (--strict-base)")
+        print(f"警告: {message}", file=sys.stderr)
+
+    if args.report_unused_base_tensors:
+        unused = unused_base_tensors(result, model.graph)
+        print(f"未被图引用的 base tensor: {len(unused)}")
+        for name in unused:
+            print(f"  {name}")
+
+    check_layer_index(result, args.max_layer_index)
+
+    if args.fail_on_unknown_tensor and result.unmatched:
+        raise ValidationError(
+            f"{len(result.unmatched)} 个 tensor 未匹配任何分类规则:\n"
+            + "\n".join(f"  {name}" for name in result.unmatched)
+        )
+
     total_layers = result.max_layer + 1 if result.max_layer >= 0 else 0
+    progress.emit(
+        "classify_done", total_layers=total_layers, tensors=len(model.graph.initializer),
+        dtypes=dtype_histogram(model.graph.initializer),
+    )
+
+    partial = False
+    if args.only_layers is not None:
+        missing = [layer for layer in args.only_layers if layer not in result.layers]
+        if missing:
+            raise ValidationError(f"--only-layers 指定的层不存在: {missing}")
+        partial = len(args.only_layers) < len(result.layers)
 
     # Step 3: 写入分片
     print("[3/5] 写入分片 external data 文件...")
+    plans = plan_shards(
+        result, args.layers_per_chunk,
+        split_base=args.split_base, extension=args.shard_extension,
+        only_layers=args.only_layers, tensors_per_chunk=args.tensors_per_chunk,
+        target_chunk_count=args.target_chunk_count, max_file_bytes=args.max_file_bytes,
+        base_include=args.base_include, shuffle_seed=args.shuffle_seed, align=args.align,
+    )
+    if args.limit_chunks is not None and args.limit_chunks < len(plans):
+        print(f"  --limit-chunks: 仅写出前 {args.limit_chunks}/{len(plans)} 个分片")
+        plans = plans[:args.limit_chunks]
+        partial = True
+    excluded_base = excluded_layers = None
+    if args.base_include is not None:
+        excluded_base = plan_excluded_base(result, args.base_include, args.shard_extension)
+    if args.only_layers is not None:
+        excluded_layers = plan_excluded_layers(result, args.only_layers, args.shard_extension)
+    excluded = [plan for plan in (excluded_base, excluded_layers) if plan is not None]
+
+    if args.clean_temp:
+        for path in clean_temp_files(args.output):
+            print(f"  已删除残留临时文件: {path.name}")
     shards = write_shards(
-        model, result, args.output, args.layers_per_chunk,
-        split_base=args.split_base,
+        model, plans, args.output,
+        overwrite=not args.no_clobber, align=args.align, fsync=args.fsync,
+        excluded=excluded,
     )
+    assign_layer_digests(result, shards, args.output)
+    for shard in shards:
+        shard.metadata = dict(args.chunk_meta) or None
+
+    if args.warn_on_large_base is not None:
+        for shard in oversized_base_shards(shards, args.warn_on_large_base):
+            print(f"警告: {shard.filename} ({shard.bytes / 1024 / 1024:.1f} MB) 超过 layer 分片"
+                  f"平均大小的 {args.warn_on_large_base:g} 倍，首个 token 前需全部下载", file=sys.stderr)
+            if not args.split_base:
+                print("  可加 --split-base 将 embed 与 lm_head 拆为独立分片", file=sys.stderr)
 
     # Step 4: 生成 manifest
     print("\n[4/5] 生成 manifest.json...")
@@ -46,12 +258,31 @@ def main():
         dtype=args.dtype,
         total_layers=total_layers,
         shards=shards,
+        tie_word_embeddings=result.tied_lm_head,
+        partial=partial,
+        included_layers=covered_layers(shards) if partial else None,
+        included_base=args.base_include,
+        shuffle_seed=args.shuffle_seed,
+        created_by=args.created_by,
+        notes=args.notes,
+    )
+    manifest_path = manifest.write(
+        args.output, args.manifest_out, compact=args.compact_manifest, fsync=args.fsync,
     )
-    manifest.write(args.output)
+    if args.embed_manifest:
+        embed_manifest(model, manifest, args.output, fsync=args.fsync)
+    if args.emit_readme:
+        write_chunks_readme(manifest, args.output)
+    progress.emit("manifest_done", path=str(manifest_path), shards=len(shards))
+
+    if args.cas_output is not None:
+        print(f"\n写入内容寻址存储: {args.cas_output}")
+        stored = store_content_addressed(shards, args.output, args.cas_output, link=args.cas_link)
+        print(f"  新写入 {stored} 个，复用 {len(shards) - stored} 个")
 
     # Step 5: 生成 config 并复制 tokenizer
     print("\n[5/5] 生成 config 和 tokenizer...")
-    num_data_files = len(shards)
+    num_data_files = len(shards) + len(excluded)
     generate_config(args.output, num_data_files, model_type=args.model_type)
 
     if args.copy_tokenizer:
@@ -62,11 +293,86 @@ def main():
     print(f"完成! 输出目录: {args.output}")
     print(f"  变体: {args.variant}")
     print(f"  分片数: {num_data_files}")
+    print(f"  总大小: {manifest.total_bytes() / 1024 / 1024:.1f} MB")
+    if args.align > 1:
+        padding = padding_bytes(plans, shards)
+        print(f"  对齐补零: {padding} 字节 "
+              f"({padding / max(manifest.total_bytes(), 1) * 100:.2f}%，--align {args.align})")
     print(f"  总层数: {total_layers}")
-    print(f"  每片层数: {args.layers_per_chunk}")
+    if args.tensors_per_chunk is not None:
+        print(f"  每片 tensor 上限: {args.tensors_per_chunk}")
+    elif args.target_chunk_count is not None:
+        print(f"  目标 layer 分片数: {args.target_chunk_count}")
+    else:
+        print(f"  每片层数: {args.layers_per_chunk}")
     print(f"  拆分 base: {'是' if args.split_base else '否'}")
+    if args.base_include is not None:
+        print(f"  写出的 base 分组: {', '.join(args.base_include)}")
+        if excluded_base is not None:
+            print(f"  其余 base 权重: {excluded_base.filename} (不列入 manifest，需自行提供)")
+    if excluded_layers is not None:
+        print(f"  未选中的层: {excluded_layers.filename} (不列入 manifest)")
+    if partial:
+        print("  注意: 仅为部分输出 (partial)")
     print(f"{'='*60}")
 
 
+def is_query_mode(args) -> bool:
+    """只检查或报告、不写分片的模式，其 stdout 输出就是结果本身。"""
+    return (
+        args.self_test or args.probe or args.verify
+        or any(mode is not None for mode in (args.validate_only, args.compare, args.plan_download))
+    )
+
+
+@contextlib.contextmanager
+def quiet_stdout(args):
+    """--quiet: 丢弃 stdout 上的进度与说明输出。
+
+    警告、错误和 --progress-json 事件在 stderr，不受影响；查询模式的结果照常输出。
+    """
+    if not args.quiet or is_query_mode(args):
+        yield
+        return
+    with open(os.devnull, "w") as devnull, contextlib.redirect_stdout(devnull):
+        yield
+
+
+def report_error(
+    err: BaseException, category: str, message: str, json_errors: bool, prefix: str = "错误: "
+) -> None:
+    """输出失败信息；json_errors 时为单个 JSON 对象，context 为 __cause__ 原因链。"""
+    if not json_errors:
+        print(f"\n{prefix}{message}", file=sys.stderr)
+        return
+    context = []
+    cause = err.__cause__
+    while cause is not None:
+        context.append(str(cause) or type(cause).__name__)
+        cause = cause.__cause__
+    print(
+        json.dumps({"error": category, "message": message, "context": context}, ensure_ascii=False),
+        file=sys.stderr,
+    )
+
+
 if __name__ == "__main__":
-    main()
+    args = parse_args()
+    try:
+        with quiet_stdout(args):
+            main(args)
+    except SharderError as e:
+        report_error(e, e.category, str(e), args.json_errors)
+        sys.exit(e.exit_code)
+    except OSError as e:
+        # 读取输入时的 OSError 已在读取处转换为 InputError，其余视为输出 I/O 错误
+        report_error(e, OutputError.category, str(e), args.json_errors)
+        sys.exit(OutputError.exit_code)
+    except KeyboardInterrupt as e:
+        report_error(e, Cancelled.category, "已中断，未完成的分片文件已删除", args.json_errors, prefix="")
+        sys.exit(Cancelled.exit_code)
+    except Exception as e:
+        if not args.json_errors:
+            raise
+        report_error(e, SharderError.category, f"{type(e).__name__}: {e}", True)
+        sys.exit(SharderError.exit_code)
@@ -1,31 +1,214 @@
 """argparse CLI for onnx-sharder。"""
 
 import argparse
+import json
+import sys
 from pathlib import Path
 
+from .parser.classify import BASE_GROUPS
+from .writer.manifest import MAX_SHUFFLE_SEED
+
+
+def _positive_int(value: str) -> int:
+    n = int(value)
+    if n < 1:
+        raise argparse.ArgumentTypeError(f"必须为正整数: {value}")
+    return n
+
+
+def _positive_float(value: str) -> float:
+    x = float(value)
+    if x <= 0:
+        raise argparse.ArgumentTypeError(f"必须为正数: {value}")
+    return x
+
+
+def _key_value(value: str) -> tuple[str, str]:
+    key, sep, val = value.partition("=")
+    if not sep or not key:
+        raise argparse.ArgumentTypeError(f"应为 key=value: {value!r}")
+    return key, val
+
+
+def _name_list(value: str) -> list[str]:
+    """逗号分隔的名称，或 @<文件> (每行一个名称，忽略空行)。"""
+    if value.startswith("@"):
+        try:
+            names = Path(value[1:]).read_text().splitlines()
+        except OSError as e:
+            raise argparse.ArgumentTypeError(f"无法读取名称列表 {value[1:]}: {e}")
+    else:
+        names = value.split(",")
+    names = [n.strip() for n in names if n.strip()]
+    if not names:
+        raise argparse.ArgumentTypeError(f"名称列表为空: {value!r}")
+    return names
+
+
+def _extension(value: str) -> str:
+    ext = value.removeprefix(".")
+    if not ext or "/" in ext or "\\" in ext:
+        raise argparse.ArgumentTypeError(f"非法扩展名: {value!r}")
+    return ext
+
+
+def _layer_list(value: str) -> list[int]:
+    try:
+        layers = sorted({int(v) for v in value.split(",") if v.strip()})
+    except ValueError:
+        raise argparse.ArgumentTypeError(f"层列表应为逗号分隔的整数: {value!r}")
+    if not layers or layers[0] < 0:
+        raise argparse.ArgumentTypeError(f"层列表应为非负整数: {value!r}")
+    return layers
+
+
+def _seed(value: str) -> int:
+    n = int(value)
+    if not 0 <= n <= MAX_SHUFFLE_SEED:
+        raise argparse.ArgumentTypeError(f"必须为 0 到 2^53-1 之间的整数 (JS 可精确表示): {value}")
+    return n
+
+
+def _base_groups(value: str) -> list[str]:
+    groups = {g.strip() for g in value.split(",") if g.strip()}
+    unknown = sorted(groups - set(BASE_GROUPS))
+    if not groups or unknown:
+        raise argparse.ArgumentTypeError(
+            f"base 分组应为 {', '.join(BASE_GROUPS)} 中的一个或多个 (逗号分隔): {value!r}"
+        )
+    return [g for g in BASE_GROUPS if g in groups]
+
+
+class _ArgumentParser(argparse.ArgumentParser):
+    """参数错误时若命令行带 --json-errors，输出 {"error": "usage", ...} 而不是 usage 文本。
+
+    参数错误发生在 --json-errors 本身被解析之前或之后都有可能，因此直接检查原始 argv。
+    """
+
+    def error(self, message: str):
+        if "--json-errors" in sys.argv[1:]:
+            print(
+                json.dumps({"error": "usage", "message": message, "context": []}, ensure_ascii=False),
+                file=sys.stderr,
+            )
+            self.exit(2)
+        super().error(message)
+
 
 def parse_args() -> argparse.Namespace:
-    parser = argparse.ArgumentParser(
+    parser = _ArgumentParser(
         description="将 ONNX 模型按 Transformer 层切分为多个 external data 文件",
     )
     parser.add_argument(
         "--input",
         type=Path,
-        required=True,
-        help="输入 ONNX 模型路径 (如 model_quantized.onnx)",
+        default=None,
+        help="输入 ONNX 模型路径 (如 model_quantized.onnx)，--manifest-only 时不需要",
     )
     parser.add_argument(
         "--output",
         type=Path,
-        required=True,
+        default=None,
         help="输出目录",
     )
+    parser.add_argument(
+        "--manifest-out",
+        type=Path,
+        default=None,
+        help="manifest.json 的写出路径 (默认: <output>/manifest.json)；分片 filename 仍相对于 --output，"
+             "--verify / --compare 时从此路径读取 manifest",
+    )
+    parser.add_argument(
+        "--compact-manifest",
+        action="store_true",
+        help="manifest.json 写成单行紧凑 JSON (默认缩进 2 格便于阅读)",
+    )
+    parser.add_argument(
+        "--manifest-only",
+        action="store_true",
+        help="不分片，仅根据输出目录中已有的 model.onnx 和分片文件重新生成 manifest.json",
+    )
+    parser.add_argument(
+        "--self-test",
+        action="store_true",
+        help="用内置微型模型端到端自检 (分片 → 校验 → 重新加载)，忽略其他参数",
+    )
+    parser.add_argument(
+        "--probe",
+        action="store_true",
+        help="只打印 --input 模型的层数、config 信息、dtype 和 base tensor，不写任何文件",
+    )
+    parser.add_argument(
+        "--verify",
+        action="store_true",
+        help="按 --output 目录中的 manifest.json 并发校验全部分片的大小与 hash，不写任何文件",
+    )
+    parser.add_argument(
+        "--validate-only",
+        type=Path,
+        default=None,
+        metavar="MANIFEST",
+        help="只检查 MANIFEST 自身的一致性 (字段、hash 格式、重复、层覆盖、派生字段)，不读取分片文件",
+    )
+    parser.add_argument(
+        "--jobs",
+        type=_positive_int,
+        default=None,
+        help="--verify / --plan-download / --compare 的并发数 (默认: 按 CPU 核数)",
+    )
+    parser.add_argument(
+        "--compare",
+        type=Path,
+        default=None,
+        metavar="REFERENCE",
+        help="按分片 hash 比较 --output 与参考输出目录 (或其 manifest.json)，列出不同、缺失和多出的分片；"
+             "两侧的分片文件都按各自的 manifest 校验，文件损坏也计为差异",
+    )
+    parser.add_argument(
+        "--plan-download",
+        type=Path,
+        default=None,
+        metavar="MANIFEST",
+        help="对比 MANIFEST 与 --output 本地目录，按下载优先级列出缺失或 hash 不符的分片",
+    )
+    parser.add_argument(
+        "--plan-json",
+        action="store_true",
+        help="--plan-download 以 JSON 输出下载计划",
+    )
+    parser.add_argument(
+        "--extract",
+        type=_name_list,
+        default=None,
+        metavar="NAMES",
+        help="只把指定的 initializer (逗号分隔，或 @文件 每行一个) 打包为 --output 下的 "
+             "extract.onnx + extract.onnx_data，不分片",
+    )
+    parser.add_argument(
+        "--verify-sources",
+        type=Path,
+        default=None,
+        metavar="SUMFILE",
+        help="分片前按 b3sum 格式的校验和文件校验输入文件，不一致时不开始分片",
+    )
     parser.add_argument(
         "--model-id",
         type=str,
-        required=True,
+        default=None,
         help="模型标识 (如 Qwen/Qwen2.5-0.5B-Instruct)",
     )
+    parser.add_argument(
+        "--created-by",
+        type=str,
+        default=None,
+        help="写入 manifest 的发布者信息 (如组织或维护者)",
+    )
+    parser.add_argument(
+        "--notes",
+        type=str,
+        default=None,
+        help="写入 manifest 的自由文本备注 (如发布说明)",
+    )
     parser.add_argument(
         "--variant",
         type=str,
@@ -40,16 +223,190 @@ def parse_args() -> argparse.Namespace:
     )
     parser.add_argument(
         "--layers-per-chunk",
-        type=int,
+        type=_positive_int,
         default=1,
         help="每个分片包含的 Transformer 层数 (默认: 1)",
     )
+    packing = parser.add_mutually_exclusive_group()
+    packing.add_argument(
+        "--tensors-per-chunk",
+        type=_positive_int,
+        default=None,
+        help="按 tensor 数打包分片：整层打包直到达到 N 个 tensor，设置后忽略 --layers-per-chunk",
+    )
+    packing.add_argument(
+        "--target-chunk-count",
+        type=_positive_int,
+        default=None,
+        help="把全部层尽量均匀地切分为 N 个 layer 分片 (不含 base)，余数层分给前面的分片，"
+             "设置后忽略 --layers-per-chunk",
+    )
+    parser.add_argument(
+        "--max-file-bytes",
+        type=_positive_int,
+        default=None,
+        metavar="BYTES",
+        help="单个分片文件的上限 (如 FAT32 的 4294967295)，按 --align 最坏情况的补零估算：超过时"
+             "自动把多层分片拆小，单层或 base 分片本身超过时报错",
+    )
     parser.add_argument(
         "--split-base",
         action=argparse.BooleanOptionalAction,
         default=True,
         help="将 base 权重拆分为 embed/lm_head 独立分片 (默认: 开启，--no-split-base 关闭)",
     )
+    clobber = parser.add_mutually_exclusive_group()
+    clobber.add_argument(
+        "--force",
+        dest="no_clobber",
+        action="store_false",
+        # 两个选项共用 dest，argparse 取先注册者的默认值，须显式给出
+        default=False,
+        help="覆盖输出目录中已存在的分片和 model.onnx (默认行为)",
+    )
+    clobber.add_argument(
+        "--no-clobber",
+        dest="no_clobber",
+        action="store_true",
+        help="若输出目录中已存在同名分片或 model.onnx 则报错退出，不写任何文件",
+    )
+    parser.add_argument(
+        "--align",
+        type=_positive_int,
+        default=1,
+        metavar="BYTES",
+        help="分片内每个 tensor 的偏移量对齐到 BYTES 的整数倍，不足处补零 (如 4096 便于 mmap，默认: 1 即紧密排列)",
+    )
+    parser.add_argument(
+        "--shuffle-seed",
+        type=_seed,
+        default=None,
+        metavar="SEED",
+        help="按种子确定性地打乱每个分片内的 tensor 顺序 (layer 分片只在层内打乱)，种子 (0 到 2^53-1) "
+             "记入 manifest；--manifest-only 时需再次给出",
+    )
+    parser.add_argument(
+        "--clean-temp",
+        action="store_true",
+        help="写入前删除输出目录中之前运行崩溃残留的 .<文件名>.<pid>.tmp 临时文件",
+    )
+    parser.add_argument(
+        "--fsync",
+        action="store_true",
+        help="每个分片、model.onnx 和 manifest.json 写完后连同所在目录 fsync 到磁盘，"
+             "保证 manifest 写出前分片已持久化 (较慢)",
+    )
+    parser.add_argument(
+        "--shard-extension",
+        type=_extension,
+        default=None,
+        help="分片文件扩展名 (如 bin → model.onnx_data_0.bin)，默认无扩展名",
+    )
+    parser.add_argument(
+        "--base-include",
+        type=_base_groups,
+        default=None,
+        metavar="GROUPS",
+        help="只把列出的 base 分组 (embed_tokens,norm,lm_head 中的若干个) 写入分片，其余写入不列入 "
+             "manifest 的 model.onnx_data_base_excluded，需自行提供；--manifest-only 时需再次给出",
+    )
+    parser.add_argument(
+        "--strict-base",
+        action="store_true",
+        help="缺少 embed_tokens / norm / lm_head 等必需 base 权重时报错 (默认只警告；"
+             "lm_head 节点不叫 /lm_head/ 的共享权重模型也会被判为缺少 lm_head)",
+    )
+    parser.add_argument(
+        "--warn-on-large-base",
+        type=_positive_float,
+        nargs="?",
+        const=3.0,
+        default=None,
+        metavar="RATIO",
+        help="embed / lm_head 分片超过 layer 分片平均大小的 RATIO 倍时给出警告 (默认倍数: 3)",
+    )
+    parser.add_argument(
+        "--max-layer-index",
+        type=_positive_int,
+        default=1000,
+        help="层号上限，超过时视为名称解析错误并报错，--manifest-only 时同样检查 (默认: 1000)",
+    )
+    parser.add_argument(
+        "--fail-on-unknown-tensor",
+        action="store_true",
+        help="存在未匹配任何分类规则的 tensor 时报错并列出名称，而不是默认归入 embed",
+    )
+    parser.add_argument(
+        "--report-unused-base-tensors",
+        action="store_true",
+        help="分类后列出 embed / norm / lm_head 中没有被任何图节点引用的 tensor",
+    )
+    parser.add_argument(
+        "--verbose-tensors",
+        action="store_true",
+        help="分类时逐个打印 tensor 名称及其归属 (embed/lm_head/norm/layer(N))",
+    )
+    parser.add_argument(
+        "--progress-json",
+        action="store_true",
+        help="在 stderr 逐行输出 JSON 进度事件 (NDJSON)，供编排程序解析",
+    )
+    parser.add_argument(
+        "--quiet",
+        action="store_true",
+        help="不输出进度与说明信息，只在 stderr 输出警告和错误 (CI 用)；--verify / --compare / "
+             "--plan-download / --validate-only / --probe / --self-test 的结果照常输出",
+    )
+    parser.add_argument(
+        "--json-errors",
+        action="store_true",
+        help='失败时在 stderr 输出单个 JSON 对象 {"error", "message", "context"}，代替文本错误信息',
+    )
+    parser.add_argument(
+        "--only-layers",
+        type=_layer_list,
+        default=None,
+        help="只为指定层生成分片 (如 0,5,6,31)，manifest 标记为 partial "
+             "(其余层权重写入不列入 manifest 的 model.onnx_data_layers_excluded)",
+    )
+    parser.add_argument(
+        "--limit-chunks",
+        type=_positive_int,
+        default=None,
+        help="只写出前 N 个分片用于快速冒烟测试，manifest 标记为 partial "
+             "(未写出的权重保留在 model.onnx 内联)",
+    )
+    parser.add_argument(
+        "--cas-output",
+        type=Path,
+        default=None,
+        metavar="DIR",
+        help="另将分片按 BLAKE3 hash 命名存入共享目录 DIR，已存在的分片跳过 (多个模型/变体共用层只存一份)",
+    )
+    parser.add_argument(
+        "--cas-link",
+        action="store_true",
+        help="--cas-output 用硬链接代替复制以节省空间；之后不能就地修改输出目录中的分片 "
+             "(如 rsync --inplace)，否则存储对象内容会与其 hash 文件名不符",
+    )
+    parser.add_argument(
+        "--chunk-meta",
+        type=_key_value,
+        action="append",
+        default=[],
+        metavar="KEY=VALUE",
+        help="为每个分片附加自定义元数据 (如 training_step=1200)，可重复指定",
+    )
+    parser.add_argument(
+        "--embed-manifest",
+        action="store_true",
+        help="同时将 manifest 写入 model.onnx 的 metadata_props (key: pb_ai.manifest)",
+    )
+    parser.add_argument(
+        "--emit-readme",
+        action="store_true",
+        help="在输出目录生成 CHUNKS.md，列出每个分片的 id、层范围、大小和 hash",
+    )
     parser.add_argument(
         "--copy-tokenizer",
         type=Path,
@@ -62,4 +419,27 @@ def parse_args() -> argparse.Namespace:
         default="llama",
         help="模型架构类型 (默认: llama)",
     )
-    return parser.parse_args()
+    args = parser.parse_args()
+    if args.self_test:
+        return args
+    if args.probe:
+        if args.input is None:
+            parser.error("--probe 需要 --input")
+        return args
+    if args.validate_only is not None:
+        return args
+    if args.extract is not None:
+        if args.input is None or args.output is None:
+            parser.error("--extract 需要 --input 和 --output")
+        return args
+    if args.verify or args.plan_download is not None or args.compare is not None:
+        if args.output is None:
+            parser.error("--verify / --plan-download / --compare 需要 --output")
+        return args
+    if args.output is None or args.model_id is None:
+        parser.error("缺少 --output 或 --model-id")
+    if args.input is None and not args.manifest_only:
+        parser.error("缺少 --input (仅 --manifest-only 模式可省略)")
+    if args.target_chunk_count is not None and args.only_layers is not None:
+        parser.error("--target-chunk-count 不能与 --only-layers 同时使用")
+    return args
@@ -0,0 +1,66 @@
+"""onnx-sharder 错误类型。
+
+每个类别对应一个固定的进程退出码，便于脚本区分失败原因:
+
+    0  成功
+    1  未分类的内部错误
+    2  命令行参数错误 (argparse 默认)
+    3  输入 (模型、校验和文件、manifest、tokenizer 等) 不存在、无法读取或无法解析
+    4  校验失败 (分类结果或 manifest 不合法)
+    5  输出 I/O 错误 (含磁盘空间不足)
+    6  完整性不匹配 (文件大小或 hash 与 manifest 不符)
+  130  被取消 (调用方取消或 Ctrl-C)
+
+category 是 --json-errors 输出中 "error" 字段的取值；命令行参数错误 (退出码 2)
+不经过这些类型，"error" 固定为 "usage" (见 cli._ArgumentParser)。
+"""
+
+
+class SharderError(Exception):
+    """所有可预期错误的基类。"""
+
+    exit_code = 1
+    category = "internal"
+
+
+class InputError(SharderError):
+    """输入不存在、无法读取或无法解析。"""
+
+    exit_code = 3
+    category = "input"
+
+
+class ValidationError(SharderError):
+    """分类结果或 manifest 不满足约束。"""
+
+    exit_code = 4
+    category = "validation"
+
+
+class OutputError(SharderError):
+    """写入输出目录失败。"""
+
+    exit_code = 5
+    category = "output"
+
+
+class IntegrityError(SharderError):
+    """文件内容与 manifest 记录不一致。"""
+
+    exit_code = 6
+    category = "integrity"
+
+
+class SizeMismatch(IntegrityError):
+    """文件大小与 manifest 记录的 bytes 不符 (未读取文件内容)。"""
+
+
+class DigestMismatch(IntegrityError):
+    """文件大小一致，但 BLAKE3 与 manifest 记录的 hash 不符。"""
+
+
+class Cancelled(SharderError):
+    """运行被取消，已写完的分片保持完整。"""
+
+    exit_code = 130
+    category = "cancelled"
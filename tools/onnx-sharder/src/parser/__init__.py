@@ -1,9 +1,21 @@
 from .onnx_loader import load_onnx_model
-from .classify import ClassifyResult, classify_initializers, print_summary
+from .classify import (
+    BASE_GROUPS, ClassifyResult, base_group_tensors, classify_initializers, missing_base_tensors,
+    print_summary, unused_base_tensors,
+)
+from .dtypes import dtype_bytes, dtype_histogram, stored_data_bytes, tensor_nbytes
 
 __all__ = [
     "load_onnx_model",
+    "BASE_GROUPS",
     "ClassifyResult",
+    "base_group_tensors",
     "classify_initializers",
+    "missing_base_tensors",
     "print_summary",
+    "unused_base_tensors",
+    "dtype_bytes",
+    "dtype_histogram",
+    "stored_data_bytes",
+    "tensor_nbytes",
 ]
@@ -17,6 +17,8 @@ from typing import Literal
 
 import onnx
 
+from .dtypes import dtype_histogram, tensor_nbytes
+
 # 匹配 initializer 名称中的层索引
 LAYER_RE = re.compile(r"^model\.layers\.(\d+)\.")
 # 匹配图节点名称中的层索引
@@ -25,6 +27,8 @@ NODE_LAYER_RE = re.compile(r"/model/layers\.(\d+)/")
 EMBED_RE = re.compile(r"^model\.embed_tokens\.")
 # 匹配最终 norm
 NORM_RE = re.compile(r"^model\.norm\.")
+# --base-include 可选的 base 分组名
+BASE_GROUPS = ("embed_tokens", "norm", "lm_head")
 
 
 @dataclass
@@ -36,6 +40,10 @@ class ClassifyResult:
     norm: list[onnx.TensorProto] = field(default_factory=list)
     layers: dict[int, list[onnx.TensorProto]] = field(default_factory=dict)
     max_layer: int = -1
+    # 图中存在 /lm_head/ 节点但没有独占的 initializer，即与 embed 共享权重
+    tied_lm_head: bool = False
+    # 未匹配任何规则、按默认归入 embed 的 tensor 名称
+    unmatched: list[str] = field(default_factory=list)
 
 
 def _build_init_to_layer_map(graph: onnx.GraphProto) -> dict[str, int]:
@@ -74,10 +82,11 @@ def classify_tensor(
     name: str,
     init_to_layer: dict[str, int],
     lm_head_inits: set[str],
-) -> tuple[Literal["embed", "lm_head", "norm", "layer"], int | None]:
+) -> tuple[Literal["embed", "lm_head", "norm", "layer", "unmatched"], int | None]:
     """将 tensor 名称分类为 embed/lm_head/norm/layer(N)。
 
     优先用 initializer 名称匹配，fallback 到图节点追踪。
+    都不匹配时返回 "unmatched"，由调用方归入 embed。
     """
     # 方式 1: 直接从名称匹配层 (model.layers.N.*)
     m = LAYER_RE.match(name)
@@ -101,38 +110,97 @@ def classify_tensor(
         return "lm_head", None
 
     # 未匹配的 base 权重默认归入 embed
-    return "embed", None
+    return "unmatched", None
 
 
 def classify_initializers(
     initializers: list[onnx.TensorProto],
     graph: onnx.GraphProto,
+    verbose: bool = False,
 ) -> ClassifyResult:
-    """将所有 initializer 按层分类。"""
+    """将所有 initializer 按层分类。
+
+    verbose=True 时逐个打印 `<name> -> <kind>` 分类决策，用于排查命名问题。
+    """
     init_to_layer = _build_init_to_layer_map(graph)
     lm_head_inits = _build_init_to_lm_head(graph)
     result = ClassifyResult()
 
     for tensor in initializers:
         kind, layer_idx = classify_tensor(tensor.name, init_to_layer, lm_head_inits)
+        if verbose:
+            label = {"layer": f"layer({layer_idx})", "unmatched": "embed (未匹配)"}.get(kind, kind)
+            print(f"    {tensor.name} -> {label}")
         if kind == "layer":
             result.layers.setdefault(layer_idx, []).append(tensor)
             result.max_layer = max(result.max_layer, layer_idx)
         elif kind == "embed":
             result.embed.append(tensor)
+        elif kind == "unmatched":
+            result.embed.append(tensor)
+            result.unmatched.append(tensor.name)
         elif kind == "lm_head":
             result.lm_head.append(tensor)
         elif kind == "norm":
             result.norm.append(tensor)
 
+    # tie_word_embeddings 模型的 lm_head 直接复用 embed_tokens 权重，
+    # 该权重已按名称归入 embed，lm_head 分组因此为空
+    result.tied_lm_head = bool(lm_head_inits) and not result.lm_head
+
+    if verbose:
+        layer_count = sum(len(ts) for ts in result.layers.values())
+        print(f"  共 {len(initializers)} 个 tensor: embed {len(result.embed)}, "
+              f"lm_head {len(result.lm_head)}, norm {len(result.norm)}, layer {layer_count}")
+
     return result
 
 
+def missing_base_tensors(result: ClassifyResult) -> list[str]:
+    """返回分类后缺失的必需 base 权重 (embed_tokens / norm / lm_head)。
+
+    lm_head 与 embed 共享权重时不算缺失。缺失通常意味着命名不符合
+    预期、权重被错误归类 (例如 embed 被归入某一层)。
+    """
+    missing = []
+    if not any(EMBED_RE.match(t.name) for t in result.embed):
+        missing.append("model.embed_tokens.*")
+    if not result.norm:
+        missing.append("model.norm.*")
+    if not result.lm_head and not result.tied_lm_head:
+        missing.append("lm_head")
+    return missing
+
+
+def base_group_tensors(result: ClassifyResult, group: str) -> list[onnx.TensorProto]:
+    """BASE_GROUPS 中某个分组的 tensor；embed_tokens 含未匹配规则、归入 embed 的 tensor。"""
+    return {"embed_tokens": result.embed, "norm": result.norm, "lm_head": result.lm_head}[group]
+
+
+def unused_base_tensors(result: ClassifyResult, graph: onnx.GraphProto) -> list[str]:
+    """base 分组 (embed / norm / lm_head) 中没有被任何图节点或图输出引用的 tensor 名称。
+
+    通常是导出时残留的缓冲区，可以考虑在导出阶段去掉；子图 (If / Loop) 内的引用也计入。
+    """
+    used = {output.name for output in graph.output}
+    pending = [graph]
+    while pending:
+        g = pending.pop()
+        for node in g.node:
+            used.update(node.input)
+            for attr in node.attribute:
+                if attr.HasField("g"):
+                    pending.append(attr.g)
+                pending.extend(attr.graphs)
+    base = [*result.embed, *result.norm, *result.lm_head]
+    return [t.name for t in base if t.name not in used]
+
+
 def print_summary(result: ClassifyResult) -> None:
     """打印分类摘要表。"""
 
     def _group_size(tensors: list[onnx.TensorProto]) -> int:
-        return sum(len(t.raw_data) if t.raw_data else 0 for t in tensors)
+        return sum(tensor_nbytes(t) for t in tensors)
 
     embed_total = _group_size(result.embed)
     lm_head_total = _group_size(result.lm_head)
@@ -145,19 +213,25 @@ def print_summary(result: ClassifyResult) -> None:
 
     print(f"  Embed tensors: {len(result.embed)} ({embed_total / 1024 / 1024:.1f} MB)")
     for tensor in result.embed:
-        size = len(tensor.raw_data) if tensor.raw_data else 0
+        size = tensor_nbytes(tensor)
         if size > 0.01 * 1024 * 1024:
             print(f"    - {tensor.name} ({size / 1024 / 1024:.2f} MB)")
 
     print(f"  LM Head tensors: {len(result.lm_head)} ({lm_head_total / 1024 / 1024:.1f} MB)")
     for tensor in result.lm_head:
-        size = len(tensor.raw_data) if tensor.raw_data else 0
+        size = tensor_nbytes(tensor)
         if size > 0.01 * 1024 / 1024:
             print(f"    - {tensor.name} ({size / 1024 / 1024:.2f} MB)")
 
+    if result.tied_lm_head:
+        print("    (lm_head 与 embed 共享权重，无独立 tensor)")
+
+    if result.unmatched:
+        print(f"    (其中 {len(result.unmatched)} 个 tensor 未匹配任何规则，默认归入 embed)")
+
     print(f"  Norm tensors: {len(result.norm)} ({norm_total / 1024 / 1024:.1f} MB)")
     for tensor in result.norm:
-        size = len(tensor.raw_data) if tensor.raw_data else 0
+        size = tensor_nbytes(tensor)
         if size > 0.01 * 1024 * 1024:
             print(f"    - {tensor.name} ({size / 1024 / 1024:.2f} MB)")
 
@@ -167,6 +241,14 @@ def print_summary(result: ClassifyResult) -> None:
 
     for layer_idx in sorted(result.layers.keys()):
         tensors = result.layers[layer_idx]
-        total = sum(len(t.raw_data) if t.raw_data else 0 for t in tensors)
+        total = sum(tensor_nbytes(t) for t in tensors)
         print(f"    Layer {layer_idx:3d}: {len(tensors):3d} tensors, {total / 1024 / 1024:.1f} MB")
+
+    all_tensors = [
+        *result.embed, *result.lm_head, *result.norm,
+        *(t for ts in result.layers.values() for t in ts),
+    ]
+    print("\n  Dtypes:")
+    for name, entry in dtype_histogram(all_tensors).items():
+        print(f"    {name:<14} {entry['count']:5d} tensors, {entry['bytes'] / 1024 / 1024:.1f} MB")
     print(f"{'='*60}\n")
@@ -0,0 +1,135 @@
+"""ONNX tensor 元素字节数与数据大小计算。
+
+不能依赖 numpy_helper.to_array(...).nbytes：部分 onnx 版本会把 BFLOAT16 / FP8
+展开成 float32，得到的字节数是实际存储的 2~4 倍。这里按 dims 乘以元素
+字节数计算，与 raw_data / external data 中的实际存储一致。
+写出没有 raw_data 的 tensor 时同理，直接转换 *_data 字段 (见 stored_data_bytes)。
+"""
+
+import array
+import math
+import sys
+
+import onnx
+from onnx import TensorProto
+
+from ..errors import ValidationError
+
+# 按名称构建，兼容尚未定义 FP8 / INT4 等枚举的旧版 onnx
+_DTYPE_BYTES_BY_NAME = {
+    "FLOAT": 4,
+    "UINT8": 1,
+    "INT8": 1,
+    "UINT16": 2,
+    "INT16": 2,
+    "INT32": 4,
+    "INT64": 8,
+    "BOOL": 1,
+    "FLOAT16": 2,
+    "DOUBLE": 8,
+    "UINT32": 4,
+    "UINT64": 8,
+    "COMPLEX64": 8,
+    "COMPLEX128": 16,
+    "BFLOAT16": 2,
+    "FLOAT8E4M3FN": 1,
+    "FLOAT8E4M3FNUZ": 1,
+    "FLOAT8E5M2": 1,
+    "FLOAT8E5M2FNUZ": 1,
+}
+# 两个元素打包为一个字节
+_PACKED_4BIT_NAMES = ("UINT4", "INT4", "FLOAT4E2M1")
+
+# 没有 raw_data 时数据所在的 TensorProto 字段 (见 onnx.proto)，未列出的定长类型都在
+# int32_data 中：每个值只取低位的元素字节数 (FLOAT16 / BFLOAT16 / FP8 存的是位模式)，
+# 4bit 类型每个值是已打包的一个字节
+_DATA_FIELD_BY_NAME = {
+    "FLOAT": "float_data",
+    "COMPLEX64": "float_data",
+    "DOUBLE": "double_data",
+    "COMPLEX128": "double_data",
+    "INT64": "int64_data",
+    "UINT32": "uint64_data",
+    "UINT64": "uint64_data",
+}
+# 无符号整数的 array typecode，按元素字节数索引
+_UINT_TYPECODES = {array.array(code).itemsize: code for code in "QLIHB"}
+
+DTYPE_BYTES: dict[int, int] = {
+    getattr(TensorProto, name): size
+    for name, size in _DTYPE_BYTES_BY_NAME.items()
+    if hasattr(TensorProto, name)
+}
+PACKED_4BIT: frozenset[int] = frozenset(
+    getattr(TensorProto, name) for name in _PACKED_4BIT_NAMES if hasattr(TensorProto, name)
+)
+
+
+def dtype_bytes(data_type: int) -> int | None:
+    """单个元素的字节数；4bit 打包类型与 STRING 等变长类型返回 None。"""
+    return DTYPE_BYTES.get(data_type)
+
+
+def tensor_nbytes(tensor: TensorProto) -> int:
+    """tensor 数据的存储字节数，不复制数据。
+
+    优先用 raw_data 长度；否则按 dims × 元素字节数计算，
+    仅对 STRING 等无法静态计算的类型回退到 numpy 转换。
+    """
+    if tensor.raw_data:
+        return len(tensor.raw_data)
+    if tensor.data_location == TensorProto.EXTERNAL:
+        for entry in tensor.external_data:
+            if entry.key == "length":
+                return int(entry.value)
+    count = math.prod(tensor.dims)
+    if tensor.data_type in PACKED_4BIT:
+        return (count + 1) // 2
+    size = dtype_bytes(tensor.data_type)
+    if size is not None:
+        return count * size
+    return onnx.numpy_helper.to_array(tensor).nbytes
+
+
+def stored_data_bytes(tensor: TensorProto) -> bytes:
+    """把没有 raw_data 的 tensor 序列化为 raw_data 的存储格式 (小端，紧密排列)。
+
+    不经过 numpy_helper.to_array，避免 BFLOAT16 / FP8 被展开成 float32；
+    STRING 等无法写成 external data 的类型，或字段长度与 tensor_nbytes 不符时抛出 ValidationError。
+    """
+    name = TensorProto.DataType.Name(tensor.data_type)
+    size = 1 if tensor.data_type in PACKED_4BIT else dtype_bytes(tensor.data_type)
+    if size is None:
+        raise ValidationError(f"tensor {tensor.name} 的类型 {name} 无法写成 external data")
+
+    field = _DATA_FIELD_BY_NAME.get(name, "int32_data")
+    values = getattr(tensor, field)
+    if field == "float_data":
+        data = array.array("f", values)
+    elif field == "double_data":
+        data = array.array("d", values)
+    else:
+        mask = (1 << (8 * size)) - 1
+        data = array.array(_UINT_TYPECODES[size], (v & mask for v in values))
+    if sys.byteorder == "big":
+        data.byteswap()
+
+    raw = data.tobytes()
+    if len(raw) != tensor_nbytes(tensor):
+        raise ValidationError(
+            f"tensor {tensor.name} ({name}) 的 {field} 共 {len(raw)} 字节，"
+            f"与形状 {list(tensor.dims)} 应有的 {tensor_nbytes(tensor)} 字节不符"
+        )
+    return raw
+
+
+def dtype_histogram(tensors) -> dict[str, dict[str, int]]:
+    """按 dtype 名称统计 tensor 个数与字节数，按字节数降序排列。"""
+    histogram: dict[str, dict[str, int]] = {}
+    for tensor in tensors:
+        entry = histogram.setdefault(
+            TensorProto.DataType.Name(tensor.data_type), {"count": 0, "bytes": 0},
+        )
+        entry["count"] += 1
+        entry["bytes"] += tensor_nbytes(tensor)
+    return dict(sorted(histogram.items(), key=lambda item: item[1]["bytes"], reverse=True))
@@ -4,15 +4,56 @@ from pathlib import Path
 
 import onnx
 
+from ..errors import InputError
 
-def load_onnx_model(path: Path) -> onnx.ModelProto:
-    """加载 ONNX 模型（含 external data）。
+
+def _check_external_data(model: onnx.ModelProto, base_dir: Path) -> None:
+    """读取 external data 前检查每个引用的文件存在且长度足够。
+
+    比 onnx 自身报错更早、更明确地区分"文件缺失"和"文件被截断"。
+    """
+    for tensor in model.graph.initializer:
+        if tensor.data_location != onnx.TensorProto.EXTERNAL:
+            continue
+        info = {entry.key: entry.value for entry in tensor.external_data}
+        data_path = base_dir / info.get("location", "")
+        if not data_path.is_file():
+            raise InputError(f"tensor {tensor.name} 引用的 external data 文件不存在: {data_path}")
+        if "length" not in info:
+            continue
+        end = int(info.get("offset", 0)) + int(info["length"])
+        size = data_path.stat().st_size
+        if end > size:
+            raise InputError(
+                f"external data 文件疑似被截断: {data_path.name} 只有 {size} 字节，"
+                f"tensor {tensor.name} 需要读到第 {end} 字节"
+            )
+
+
+def load_onnx_model(path: Path, load_external_data: bool = True) -> onnx.ModelProto:
+    """加载 ONNX 模型（默认含 external data）。
 
     使用 load_external_data=True 确保所有权重数据都加载到内存中，
-    即使原模型使用了 external data 格式。
+    即使原模型使用了 external data 格式。只需图结构和 initializer
+    元信息时可传 False，跳过读取权重。
     """
+    if not path.is_file():
+        raise InputError(f"输入模型不存在: {path}")
+    if path.stat().st_size == 0:
+        raise InputError(f"输入模型是空文件: {path}")
     model_path = str(path)
-    model = onnx.load(model_path, load_external_data=True)
+    try:
+        model = onnx.load(model_path, load_external_data=False)
+    except Exception as e:
+        raise InputError(
+            f"无法解析 ONNX 模型 {path} (文件损坏、被截断或不是 ONNX protobuf): {e}"
+        ) from e
+    if load_external_data:
+        _check_external_data(model, path.parent)
+        try:
+            onnx.load_external_data_for_model(model, str(path.parent))
+        except Exception as e:
+            raise InputError(f"读取 {path.name} 的 external data 失败: {e}") from e
     print(f"已加载模型: {path.name}")
     print(f"  IR version: {model.ir_version}")
     opsets = [f'{o.domain or "ai.onnx"}:{o.version}' for o in model.opset_import]
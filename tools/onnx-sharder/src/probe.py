@@ -0,0 +1,61 @@
+"""--probe: 只读取图结构并打印模型概况，不写任何文件。
+
+用于正式分片前快速确认 "这是什么模型、有多少层"。
+"""
+
+import json
+import sys
+from pathlib import Path
+
+from .parser import classify_initializers, dtype_histogram, load_onnx_model
+
+
+def _find_config(model_path: Path) -> Path | None:
+    """查找模型旁的 config.json (HF 仓库中 ONNX 文件常位于 onnx/ 子目录)。"""
+    for directory in (model_path.parent, model_path.parent.parent):
+        candidate = directory / "config.json"
+        if candidate.is_file():
+            return candidate
+    return None
+
+
+def run_probe(model_path: Path) -> None:
+    """打印检测到的层数、config 信息、dtype 分布和 base tensor 名称。"""
+    model = load_onnx_model(model_path, load_external_data=False)
+    result = classify_initializers(list(model.graph.initializer), model.graph)
+    tensor_layers = result.max_layer + 1
+
+    print(f"\n{'='*60}")
+    print(f"  检测到层数 (tensor): {tensor_layers}")
+
+    config_path = _find_config(model_path)
+    if config_path is None:
+        print("  config.json: 未找到")
+    else:
+        try:
+            config = json.loads(config_path.read_text())
+        except (OSError, json.JSONDecodeError) as e:
+            print(f"  config.json: 无法解析 ({e})")
+        else:
+            config_layers = config.get("num_hidden_layers")
+            print(f"  config.json: {config_path}")
+            print(f"    model_type: {config.get('model_type', '?')}")
+            print(f"    num_hidden_layers: {config_layers}")
+            if config_layers is not None and config_layers != tensor_layers:
+                print(f"    警告: 与 tensor 检测到的层数 {tensor_layers} 不一致", file=sys.stderr)
+
+    dtypes = dtype_histogram(model.graph.initializer)
+    print("  dtype: " + ", ".join(
+        f"{name}×{entry['count']} ({entry['bytes'] / 1024 / 1024:.1f} MB)"
+        for name, entry in dtypes.items()
+    ))
+
+    print("  Base tensors:")
+    for label, tensors in (
+        ("embed", result.embed), ("norm", result.norm), ("lm_head", result.lm_head),
+    ):
+        names = ", ".join(t.name for t in tensors) or "-"
+        print(f"    {label}: {names}")
+    if result.tied_lm_head:
+        print("    (lm_head 与 embed 共享权重)")
+    print(f"{'='*60}")
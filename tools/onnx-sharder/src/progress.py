@@ -0,0 +1,30 @@
+"""结构化进度事件 (NDJSON，输出到 stderr)。
+
+开启 --progress-json 后，每个生命周期事件输出一行 JSON，供编排进程
+实时解析；stdout 上的人类可读输出不受影响。事件:
+
+    load_done       {"initializers": int}
+    classify_done   {"total_layers": int, "tensors": int,
+                     "dtypes": {<dtype>: {"count": int, "bytes": int}}}
+    shard_started   {"id": str, "filename": str}
+    shard_done      {"id": str, "bytes": int, "ms": float}
+    manifest_done   {"path": str, "shards": int}
+"""
+
+import json
+import sys
+
+_enabled = False
+
+
+def enable() -> None:
+    """开启进度事件输出。"""
+    global _enabled
+    _enabled = True
+
+
+def emit(event: str, **fields) -> None:
+    """输出一个事件；未开启时为空操作。"""
+    if not _enabled:
+        return
+    print(json.dumps({"event": event, **fields}, ensure_ascii=False), file=sys.stderr, flush=True)
@@ -0,0 +1,52 @@
+"""按 manifest 逐个读出分片中的 tensor，把分片集合还原为平铺的模型视图。
+
+不在磁盘上合并分片，也不一次性加载全部权重：按 manifest 中的分片顺序
+(embed → layer → lm_head) 依次打开分片文件，按偏移量读出每个 tensor 后即关闭。
+"""
+
+import copy
+from collections.abc import Iterator
+from pathlib import Path
+
+from onnx import TensorProto
+
+from .errors import InputError
+from .parser import load_onnx_model
+from .writer import ModelManifest
+from .writer.layer_digest import external_range
+
+
+def iter_tensors(manifest: ModelManifest, shard_dir: Path) -> Iterator[tuple[str, TensorProto]]:
+    """产出 (名称, 数据已内联的 TensorProto)。
+
+    shard_dir 为分片及 model.onnx 所在目录，manifest 可以来自任意位置 (如 --manifest-out)。
+    先产出仍内联在 model.onnx 中的 tensor，再按分片顺序、分片内按偏移量产出
+    external data tensor。产出的 TensorProto 都是副本，修改它们不影响 model.onnx 的图。
+    """
+    model = load_onnx_model(shard_dir / "model.onnx", load_external_data=False)
+
+    by_location: dict[str, list[tuple[int, int, TensorProto]]] = {}
+    for tensor in model.graph.initializer:
+        location = external_range(tensor)
+        if location is None:
+            yield tensor.name, copy.deepcopy(tensor)
+            continue
+        filename, offset, length = location
+        by_location.setdefault(filename, []).append((offset, length, tensor))
+
+    for shard in manifest.shards:
+        members = sorted(by_location.pop(shard.filename, []), key=lambda m: m[0])
+        with open(shard.resolve(shard_dir), "rb") as f:
+            for offset, length, tensor in members:
+                f.seek(offset)
+                data = f.read(length)
+                if len(data) != length:
+                    raise InputError(f"{shard.filename} 在偏移 {offset} 处被截断 ({tensor.name})")
+                inlined = copy.deepcopy(tensor)
+                del inlined.external_data[:]
+                inlined.data_location = TensorProto.DEFAULT
+                inlined.raw_data = data
+                yield tensor.name, inlined
+
+    if by_location:
+        raise InputError(f"model.onnx 引用了 manifest 中没有的文件: {', '.join(sorted(by_location))}")
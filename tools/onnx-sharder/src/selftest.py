@@ -0,0 +1,118 @@
+"""内置自检：构造一个微型模型，完整走一遍 分类 → 分片 → 校验 → 重新加载。
+
+不依赖任何外部模型文件，用于确认当前环境 (onnx / blake3 版本、文件系统)
+下工具端到端可用，方便附在问题报告中。
+"""
+
+import tempfile
+from pathlib import Path
+
+import numpy as np
+import onnx
+from onnx import TensorProto, helper, numpy_helper
+
+from .errors import IntegrityError
+from .parser import classify_initializers
+from .reader import iter_tensors
+from .writer import ModelManifest, plan_shards, write_shards
+
+HIDDEN = 4
+VOCAB = 8
+NUM_LAYERS = 2
+
+
+def build_tiny_model() -> onnx.ModelProto:
+    """构造 embed → N × MatMul → norm → lm_head 的微型 Llama 风格模型。
+
+    节点命名沿用导出模型的 /model/layers.N/ 与 /lm_head/ 结构，
+    层权重使用量化后的 onnx::MatMul_XXXX 命名，覆盖图节点反向追踪路径。
+    """
+    rng = np.random.default_rng(0)
+
+    def weight(name: str, *shape: int) -> TensorProto:
+        return numpy_helper.from_array(rng.standard_normal(shape).astype(np.float32), name)
+
+    initializers = [weight("model.embed_tokens.weight", VOCAB, HIDDEN)]
+    nodes = [helper.make_node(
+        "Gather", ["model.embed_tokens.weight", "input_ids"], ["h0"],
+        name="/model/embed_tokens/Gather",
+    )]
+    for i in range(NUM_LAYERS):
+        initializers.append(weight(f"onnx::MatMul_{100 + i}", HIDDEN, HIDDEN))
+        nodes.append(helper.make_node(
+            "MatMul", [f"h{i}", f"onnx::MatMul_{100 + i}"], [f"h{i + 1}"],
+            name=f"/model/layers.{i}/mlp/MatMul",
+        ))
+    initializers.append(weight("model.norm.weight", HIDDEN))
+    nodes.append(helper.make_node(
+        "Mul", [f"h{NUM_LAYERS}", "model.norm.weight"], ["normed"], name="/model/norm/Mul",
+    ))
+    initializers.append(weight("onnx::MatMul_999", HIDDEN, VOCAB))
+    nodes.append(helper.make_node(
+        "MatMul", ["normed", "onnx::MatMul_999"], ["logits"], name="/lm_head/MatMul",
+    ))
+
+    graph = helper.make_graph(
+        nodes,
+        "pb-ai-selftest",
+        [helper.make_tensor_value_info("input_ids", TensorProto.INT64, [1, "seq"])],
+        [helper.make_tensor_value_info("logits", TensorProto.FLOAT, [1, "seq", VOCAB])],
+        initializer=initializers,
+    )
+    return helper.make_model(graph)
+
+
+def _check(label: str, ok: bool) -> bool:
+    print(f"  [{'PASS' if ok else 'FAIL'}] {label}")
+    return ok
+
+
+def run_self_test() -> bool:
+    """执行自检，返回是否全部通过。"""
+    model = build_tiny_model()
+    expected = {t.name: numpy_helper.to_array(t).tobytes() for t in model.graph.initializer}
+
+    with tempfile.TemporaryDirectory(prefix="onnx-sharder-selftest-") as tmp:
+        output_dir = Path(tmp)
+
+        print("\n[1/3] 分类并写入分片...")
+        result = classify_initializers(list(model.graph.initializer), model.graph)
+        shards = write_shards(model, plan_shards(result, layers_per_chunk=1), output_dir)
+        manifest = ModelManifest(
+            model_id="pb-ai/selftest",
+            variant="base",
+            framework="onnxruntime-web",
+            dtype="fp32",
+            total_layers=result.max_layer + 1,
+            shards=shards,
+        )
+        manifest.write(output_dir)
+
+        print("\n[2/3] 校验分片...")
+        ok = _check(
+            "分片布局为 embed + 每层一个 layer + lm_head",
+            [s.id for s in shards]
+            == ["embed", *(f"layer_{i}" for i in range(NUM_LAYERS)), "lm_head"],
+        )
+        for shard in shards:
+            try:
+                shard.verify_file(shard.resolve(output_dir))
+                error = None
+            except IntegrityError as e:
+                error = e
+            ok &= _check(f"{shard.filename} 大小与 hash 一致", error is None)
+            if error is not None:
+                print(f"         {error}")
+
+        print("\n[3/3] 重新加载并比对权重...")
+        reloaded = onnx.load(str(output_dir / "model.onnx"), load_external_data=True)
+        actual = {t.name: numpy_helper.to_array(t).tobytes() for t in reloaded.graph.initializer}
+        ok &= _check(f"{len(expected)} 个 initializer 逐字节一致", actual == expected)
+
+        streamed = {
+            name: numpy_helper.to_array(t).tobytes() for name, t in iter_tensors(manifest, output_dir)
+        }
+        ok &= _check("按 manifest 逐分片读出的 tensor 与原始权重一致", streamed == expected)
+
+    print(f"\n自检{'通过' if ok else '失败'}")
+    return ok
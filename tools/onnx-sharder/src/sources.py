@@ -0,0 +1,48 @@
+"""--verify-sources: 分片前按校验和文件检查输入文件，防止源文件损坏被原样分片。
+
+校验和文件使用 b3sum 的输出格式，每行 `<blake3 hex>  <路径>`，
+相对路径相对于校验和文件所在目录解析；空行和 # 开头的行忽略。
+"""
+
+from pathlib import Path
+
+from .errors import InputError, IntegrityError
+from .writer.manifest import blake3_file
+
+
+def _parse_sum_file(sum_file: Path) -> list[tuple[str, Path]]:
+    try:
+        lines = sum_file.read_text().splitlines()
+    except OSError as e:
+        raise InputError(f"无法读取校验和文件 {sum_file}: {e}") from e
+    entries = []
+    for lineno, line in enumerate(lines, 1):
+        line = line.strip()
+        if not line or line.startswith("#"):
+            continue
+        digest, sep, name = line.partition("  ")
+        if not sep or not name:
+            raise InputError(f"{sum_file}:{lineno}: 应为 `<hash>  <路径>`: {line!r}")
+        entries.append((digest.lower(), sum_file.parent / name.lstrip("*")))
+    if not entries:
+        raise InputError(f"校验和文件为空: {sum_file}")
+    return entries
+
+
+def verify_sources(sum_file: Path) -> None:
+    """逐个校验 sum_file 中列出的源文件，任何文件缺失或 hash 不符时抛出异常。"""
+    entries = _parse_sum_file(sum_file)
+    print(f"\n校验 {len(entries)} 个源文件: {sum_file}")
+    mismatched = []
+    for digest, path in entries:
+        if not path.is_file():
+            raise InputError(f"校验和文件中列出的源文件不存在: {path}")
+        try:
+            ok = blake3_file(path) == digest
+        except OSError as e:
+            raise InputError(f"无法读取源文件 {path}: {e}") from e
+        print(f"  [{'PASS' if ok else 'FAIL'}] {path}")
+        if not ok:
+            mismatched.append(str(path))
+    if mismatched:
+        raise IntegrityError(f"{len(mismatched)} 个源文件 hash 不符: {', '.join(mismatched)}")
@@ -0,0 +1,65 @@
+"""--validate-only: 只检查 manifest 自身的一致性，不读取分片文件。
+
+适合在 CI 中把关已发布的 manifest。分片文件是否完好由 --verify 负责；
+这里只看 manifest 内部是否自洽:
+
+1. 字段齐全、类型正确，hash 带 blake3: 前缀 (ModelManifest.validate 的全部规则)
+2. 非 partial 时 layer 分片覆盖 [0, total_layers) 且没有空缺
+3. 派生字段 manifest_digest / depends_on 与分片列表一致 (没有手工改动后遗漏更新)
+"""
+
+import json
+from pathlib import Path
+
+from .errors import InputError, ValidationError
+from .writer import ModelManifest, covered_layers, shard_dependencies
+
+
+def _load_manifest(manifest_path: Path) -> tuple[dict, ModelManifest]:
+    if not manifest_path.is_file():
+        raise InputError(f"manifest 不存在: {manifest_path}")
+    try:
+        data = json.loads(manifest_path.read_text())
+    except OSError as e:
+        raise InputError(f"无法读取 manifest {manifest_path}: {e}") from e
+    except json.JSONDecodeError as e:
+        raise InputError(f"无法解析 manifest {manifest_path}: {e}") from e
+    try:
+        return data, ModelManifest.from_dict(data)
+    except (KeyError, TypeError, AttributeError, ValueError) as e:
+        raise ValidationError(f"manifest 字段缺失或类型错误: {e!r}") from e
+
+
+def run_validate(manifest_path: Path) -> None:
+    """检查 manifest，发现第一个问题即抛出 ValidationError。"""
+    data, manifest = _load_manifest(manifest_path)
+    print(f"\n检查 manifest: {manifest_path}")
+
+    for entry in data["shards"]:
+        if not str(entry["hash"]).startswith("blake3:"):
+            raise ValidationError(f"分片 {entry['id']} 的 hash 缺少 blake3: 前缀: {entry['hash']!r}")
+    try:
+        manifest.validate()
+    except (TypeError, ValueError) as e:
+        # validate 没有逐一检查的字段类型错误 (如 id 为数组) 同样按校验失败处理
+        raise ValidationError(f"manifest 字段类型错误: {e!r}") from e
+
+    if not manifest.partial:
+        gaps = sorted(set(range(manifest.total_layers)) - set(covered_layers(manifest.shards)))
+        if gaps:
+            raise ValidationError(
+                f"层 {', '.join(map(str, gaps))} 不在任何 layer 分片中，"
+                f"但 manifest 未标记 partial (total_layers {manifest.total_layers})"
+            )
+
+    recorded = data.get("manifest_digest")
+    if recorded is not None and recorded != f"blake3:{manifest.manifest_digest()}":
+        raise ValidationError(f"manifest_digest 与分片 hash 不一致: {recorded}")
+    dependencies = shard_dependencies(manifest.shards)
+    for entry in data["shards"]:
+        if "depends_on" in entry and entry["depends_on"] != dependencies.get(entry["id"]):
+            raise ValidationError(
+                f"分片 {entry['id']} 的 depends_on 与分片列表不一致: {entry['depends_on']}"
+            )
+
+    print(f"manifest 合法: {len(manifest.shards)} 个分片，{manifest.total_layers} 层")
@@ -0,0 +1,162 @@
+"""--verify / --plan-download / --compare: 按 manifest.json 校验本地目录中的分片。
+
+各分片互相独立，用线程池并发计算 hash (BLAKE3 计算期间释放 GIL)；
+每个文件按块流式读取，不整体载入内存。结果按 manifest 中的分片顺序输出，
+与并发调度无关。
+"""
+
+import json
+from concurrent.futures import ThreadPoolExecutor
+from pathlib import Path
+
+from .errors import IntegrityError, InputError
+from .writer import Shard
+
+
+def _load_shards(manifest_path: Path) -> list[Shard]:
+    if not manifest_path.is_file():
+        raise InputError(f"manifest 不存在: {manifest_path}")
+    try:
+        text = manifest_path.read_text()
+    except OSError as e:
+        raise InputError(f"无法读取 manifest {manifest_path}: {e}") from e
+    try:
+        data = json.loads(text)
+        return [Shard.from_dict(d) for d in data["shards"]]
+    except (json.JSONDecodeError, KeyError, TypeError, AttributeError, ValueError) as e:
+        # 字段类型不对 (如 hash 不是字符串、layer_digests 的 key 不是层号) 同样视为无法解析
+        raise InputError(f"无法解析 manifest {manifest_path}: {e}") from e
+
+
+def _verify_one(shard: Shard, output_dir: Path) -> str | None:
+    """校验单个分片，通过时返回 None，否则返回失败原因。"""
+    try:
+        shard.verify_file(shard.resolve(output_dir))
+    except IntegrityError as e:
+        return str(e)
+    except OSError as e:
+        return f"分片 {shard.id} 无法读取: {e}"
+    return None
+
+
+def _verify_all(shards: list[Shard], output_dir: Path, jobs: int | None) -> list[str | None]:
+    with ThreadPoolExecutor(max_workers=jobs) as pool:
+        return list(pool.map(lambda s: _verify_one(s, output_dir), shards))
+
+
+def _download_order(shard: Shard) -> tuple[int, int]:
+    """embed → layer (按层号升序) → lm_head，先补齐可运行的前缀。"""
+    rank = {"embed": 0, "layer": 1, "lm_head": 2}[shard.kind]
+    return rank, shard.layer_range[0] if shard.layer_range is not None else 0
+
+
+def run_verify(
+    output_dir: Path, jobs: int | None = None, manifest_path: Path | None = None,
+) -> bool:
+    """校验 manifest 列出的全部分片 (相对于 output_dir)，返回是否全部通过。
+
+    manifest_path 为空时读取 output_dir/manifest.json。
+    """
+    shards = _load_shards(manifest_path or output_dir / "manifest.json")
+    print(f"\n校验 {len(shards)} 个分片: {output_dir}")
+
+    failures = _verify_all(shards, output_dir, jobs)
+
+    for shard, failure in zip(shards, failures):
+        print(f"  [{'PASS' if failure is None else 'FAIL'}] {shard.filename}")
+        if failure is not None:
+            print(f"         {failure}")
+
+    failed = sum(f is not None for f in failures)
+    print(f"\n校验{'通过' if failed == 0 else f'失败: {failed}/{len(shards)} 个分片不一致'}")
+    return failed == 0
+
+
+def plan_download(
+    manifest_path: Path, local_dir: Path, jobs: int | None = None, as_json: bool = False,
+) -> None:
+    """列出 local_dir 中仍需下载的分片 (缺失或 hash 不符)，按下载优先级排序。
+
+    filename 相对于分片目录 (与 manifest 所在位置无关，见 --manifest-out)，
+    客户端用分片的 base URL 拼接下载地址即可断点续传。
+    """
+    shards = _load_shards(manifest_path)
+    failures = _verify_all(shards, local_dir, jobs)
+    pending = sorted(
+        (
+            (shard, "missing" if not shard.resolve(local_dir).exists() else "mismatch")
+            for shard, failure in zip(shards, failures)
+            if failure is not None
+        ),
+        key=lambda item: _download_order(item[0]),
+    )
+    pending_bytes = sum(shard.bytes for shard, _ in pending)
+
+    if as_json:
+        print(json.dumps({
+            "manifest": str(manifest_path),
+            "pending_bytes": pending_bytes,
+            "shards": [
+                {
+                    "id": shard.id,
+                    "filename": shard.filename,
+                    "bytes": shard.bytes,
+                    "hash": f"blake3:{shard.hash}",
+                    "reason": reason,
+                }
+                for shard, reason in pending
+            ],
+        }, indent=2, ensure_ascii=False))
+        return
+
+    print(f"\n待下载 {len(pending)}/{len(shards)} 个分片 "
+          f"({pending_bytes / 1024 / 1024:.1f} MB): {local_dir}")
+    for shard, reason in pending:
+        print(f"  {shard.filename}\t{reason}\t{shard.bytes}")
+
+
+def run_compare(
+    output_dir: Path, reference: Path, jobs: int | None = None, manifest_path: Path | None = None,
+) -> bool:
+    """按 manifest 比较 output_dir 与参考输出，返回分片集合是否完全一致。
+
+    reference 可以是输出目录或 manifest 文件 (此时分片文件在 manifest 所在目录)；
+    manifest_path 为空时读取 output_dir/manifest.json。按分片 id 对齐，报告 hash 不同、
+    缺失和多出的分片。两侧的分片文件都先按各自的 manifest 校验 (同 --verify)，
+    文件缺失或与 manifest 不符也计为差异，因此比较一致时两侧文件逐字节相同。
+    """
+    if reference.is_dir():
+        reference_dir, reference_manifest = reference, reference / "manifest.json"
+    else:
+        reference_dir, reference_manifest = reference.parent, reference
+    actual = {s.id: s for s in _load_shards(manifest_path or output_dir / "manifest.json")}
+    expected = {s.id: s for s in _load_shards(reference_manifest)}
+    print(f"\n比较 {output_dir} 与参考 {reference_manifest}")
+
+    actual_failures = dict(zip(actual, _verify_all(list(actual.values()), output_dir, jobs)))
+    expected_failures = dict(zip(expected, _verify_all(list(expected.values()), reference_dir, jobs)))
+
+    differences = 0
+    for shard_id, ref in expected.items():
+        shard = actual.get(shard_id)
+        if shard is None:
+            print(f"  [MISSING] {shard_id} ({ref.filename})")
+        elif shard.hash != ref.hash or shard.filename != ref.filename:
+            print(f"  [DIFF]    {shard_id}: {shard.filename} blake3:{shard.hash}")
+            print(f"            参考      {ref.filename} blake3:{ref.hash}")
+        elif actual_failures[shard_id] is not None or expected_failures[shard_id] is not None:
+            print(f"  [BAD]     {shard_id}")
+            for side, failure in (("输出", actual_failures[shard_id]), ("参考", expected_failures[shard_id])):
+                if failure is not None:
+                    print(f"            {side}: {failure}")
+        else:
+            print(f"  [SAME]    {shard_id}")
+            continue
+        differences += 1
+    for shard_id, shard in actual.items():
+        if shard_id not in expected:
+            print(f"  [EXTRA]   {shard_id} ({shard.filename})")
+            differences += 1
+
+    print(f"\n比较{'一致' if differences == 0 else f'不一致: {differences} 处差异'}")
+    return differences == 0
@@ -1,12 +1,38 @@
-from .shard_writer import write_shards
-from .manifest import Shard, ShardKind, ModelManifest
+from .shard_writer import (
+    ShardPlan, plan_shards, write_shards, layer_chunk_ranges, embed_manifest, clean_temp_files,
+    padding_bytes, plan_excluded_base, plan_excluded_layers,
+)
+from .manifest import (
+    Shard, ShardKind, ModelManifest, covered_layers, oversized_base_shards, shard_dependencies,
+)
+from .regenerate import rebuild_shards
+from .layer_digest import assign_layer_digests
+from .extract import extract_tensors
+from .readme import write_chunks_readme
+from .cas import store_content_addressed
 from .config_gen import generate_config, copy_tokenizer
 
 __all__ = [
+    "ShardPlan",
+    "plan_shards",
     "write_shards",
+    "layer_chunk_ranges",
+    "embed_manifest",
+    "clean_temp_files",
+    "padding_bytes",
+    "plan_excluded_base",
+    "plan_excluded_layers",
     "Shard",
     "ShardKind",
     "ModelManifest",
+    "covered_layers",
+    "oversized_base_shards",
+    "shard_dependencies",
+    "rebuild_shards",
+    "assign_layer_digests",
+    "extract_tensors",
+    "write_chunks_readme",
+    "store_content_addressed",
     "generate_config",
     "copy_tokenizer",
 ]
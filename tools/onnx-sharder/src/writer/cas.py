@@ -0,0 +1,49 @@
+"""--cas-output: 把分片按内容 hash 另存到共享目录，多个模型/变体共用的分片只存一份。
+
+文件名为分片 BLAKE3 的 hex (不含 blake3: 前缀)，manifest 中的 id → hash
+即为逻辑分片到存储对象的映射。目标已存在时跳过 (内容由文件名保证)。
+
+默认复制：存储对象与输出目录中的分片互相独立，之后就地修改输出文件
+(rsync --inplace、手工编辑) 不会破坏"文件名即内容 hash"。link=True
+(--cas-link) 时改用硬链接节省空间，跨文件系统时退回复制。
+"""
+
+import os
+import shutil
+from pathlib import Path
+
+from .manifest import Shard
+from .shard_writer import temp_path
+
+
+def store_content_addressed(
+    shards: list[Shard], output_dir: Path, cas_dir: Path, link: bool = False,
+) -> int:
+    """将 output_dir 中的分片存入 cas_dir，返回新写入的分片数。
+
+    link 为 True 时硬链接到输出分片；此后不能就地修改输出目录中的分片文件。
+    """
+    cas_dir.mkdir(parents=True, exist_ok=True)
+    stored = 0
+    for shard in shards:
+        target = cas_dir / shard.hash
+        if target.exists():
+            print(f"  {shard.filename}: 已存在 {target.name}，跳过")
+            continue
+        source = shard.resolve(output_dir)
+        tmp = temp_path(target)
+        try:
+            if link:
+                try:
+                    os.link(source, tmp)
+                except OSError:
+                    shutil.copyfile(source, tmp)
+            else:
+                shutil.copyfile(source, tmp)
+            tmp.replace(target)
+        except BaseException:
+            tmp.unlink(missing_ok=True)
+            raise
+        stored += 1
+        print(f"  {shard.filename} -> {target}")
+    return stored
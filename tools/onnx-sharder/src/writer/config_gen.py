@@ -2,8 +2,11 @@
 
 import json
 import shutil
+import sys
 from pathlib import Path
 
+from ..errors import InputError
+
 
 def generate_config(
     output_dir: Path,
@@ -38,9 +41,14 @@ def copy_tokenizer(src_dir: Path, output_dir: Path) -> list[Path]:
         src = src_dir / name
         if src.exists():
             dst = output_dir / name
-            shutil.copy2(src, dst)
+            try:
+                data = src.read_bytes()
+            except OSError as e:
+                raise InputError(f"无法读取 {src}: {e}") from e
+            dst.write_bytes(data)
+            shutil.copystat(src, dst)
             copied.append(dst)
             print(f"已复制 {name}")
         else:
-            print(f"警告: 未找到 {src}")
+            print(f"警告: 未找到 {src}", file=sys.stderr)
     return copied
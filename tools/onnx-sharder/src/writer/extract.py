@@ -0,0 +1,46 @@
+"""--extract: 把指定名称的 initializer 原样打包为一个独立的 ONNX 文件。
+
+不按层边界，只包含列出的 tensor，用于构建定制子模型或排查个别权重。
+输出 extract.onnx (无节点，仅 initializer) 加单个 extract.onnx_data，
+可直接用 onnx.load 读回。
+"""
+
+import copy
+from pathlib import Path
+
+import onnx
+from onnx import helper
+
+from ..errors import ValidationError
+from ..parser.dtypes import tensor_nbytes
+from .shard_writer import write_data_file
+
+EXTRACT_MODEL = "extract.onnx"
+EXTRACT_DATA = "extract.onnx_data"
+
+
+def extract_tensors(model: onnx.ModelProto, names: list[str], output_dir: Path) -> Path:
+    """将 names 中的 initializer 写入 output_dir，返回 extract.onnx 路径。
+
+    任一名称不存在时在写入任何文件前抛出 ValidationError。
+    """
+    by_name = {t.name: t for t in model.graph.initializer}
+    missing = [name for name in names if name not in by_name]
+    if missing:
+        raise ValidationError(f"模型中不存在以下 tensor: {', '.join(missing)}")
+
+    tensors = [copy.deepcopy(by_name[name]) for name in dict.fromkeys(names)]
+    output_dir.mkdir(parents=True, exist_ok=True)
+
+    inline = [t for t in tensors if tensor_nbytes(t) == 0]
+    total_bytes = write_data_file(
+        [t for t in tensors if tensor_nbytes(t) > 0], output_dir / EXTRACT_DATA,
+    )
+    graph = helper.make_graph([], "extract", [], [], initializer=tensors)
+    model_path = output_dir / EXTRACT_MODEL
+    onnx.save(helper.make_model(graph), str(model_path))
+
+    print(f"  {EXTRACT_DATA}: {len(tensors) - len(inline)} 个 tensor ({total_bytes / 1024 / 1024:.1f} MB)")
+    if inline:
+        print(f"  {len(inline)} 个 0 字节 tensor 内联在 {EXTRACT_MODEL} 中")
+    return model_path
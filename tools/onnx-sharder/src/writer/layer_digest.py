@@ -0,0 +1,52 @@
+"""为跨多层的 layer 分片计算逐层 hash。
+
+分片内同一层的 tensor 连续存放 (见 plan_shards)，每层对应一个字节区间
+[首个 tensor 偏移, 末个 tensor 结束)，区间内的对齐补零一并计入。整片 hash
+不符时，客户端可逐层比对区间 hash，把损坏定位到具体的层。
+
+区间只依赖 initializer 的 external data 引用，写出后与 --manifest-only
+重新生成时得到的结果相同。
+"""
+
+from pathlib import Path
+
+from onnx import TensorProto
+
+from ..parser.classify import ClassifyResult
+from .manifest import LayerDigest, Shard, blake3_range
+
+
+def external_range(tensor: TensorProto) -> tuple[str, int, int] | None:
+    """返回 tensor 的 (location, offset, length)，内联 tensor 返回 None。"""
+    if tensor.data_location != TensorProto.EXTERNAL:
+        return None
+    entries = {entry.key: entry.value for entry in tensor.external_data}
+    if "location" not in entries:
+        return None
+    return entries["location"], int(entries.get("offset", 0)), int(entries.get("length", 0))
+
+
+def assign_layer_digests(classify_result: ClassifyResult, shards: list[Shard], output_dir: Path) -> None:
+    """为 layer_range 跨多层的分片填充 layer_digests，单层分片的整片 hash 已足够。"""
+    for shard in shards:
+        if shard.layer_range is None or shard.layer_range[0] == shard.layer_range[1]:
+            continue
+        digests: dict[int, LayerDigest] = {}
+        for layer_idx in range(shard.layer_range[0], shard.layer_range[1] + 1):
+            ranges = [
+                (offset, offset + length)
+                for location, offset, length in filter(None, map(
+                    external_range, classify_result.layers.get(layer_idx, []),
+                ))
+                if location == shard.filename
+            ]
+            if not ranges:
+                continue
+            start = min(r[0] for r in ranges)
+            length = max(r[1] for r in ranges) - start
+            digests[layer_idx] = LayerDigest(
+                offset=start,
+                length=length,
+                hash=blake3_range(shard.resolve(output_dir), start, length),
+            )
+        shard.layer_digests = digests or None
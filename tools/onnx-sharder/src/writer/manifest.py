@@ -1,13 +1,88 @@
 """生成 manifest.json，v0.2 schema。"""
 
 import json
+import os
+import re
 from dataclasses import dataclass
-from pathlib import Path
+from pathlib import Path, PurePosixPath, PureWindowsPath
 from typing import Literal
 
+import blake3
+
+from ..errors import DigestMismatch, SizeMismatch, ValidationError
+
 
 ShardKind = Literal["embed", "layer", "lm_head"]
 
+# BLAKE3-256 摘要的小写 hex (manifest 中去掉 blake3: 前缀后的形式)
+BLAKE3_HEX_RE = re.compile(r"[0-9a-f]{64}")
+
+# 建议并发下载数上限，与 packages/inference 的 DEFAULT_CONCURRENCY 对齐
+MAX_SUGGESTED_PARALLELISM = 6
+
+# shuffle_seed 上限：JSON 数字在 JS 中是 double，超过 2^53-1 会丢失精度
+MAX_SHUFFLE_SEED = 2**53 - 1
+
+
+def blake3_file(path: Path) -> str:
+    """计算文件的 BLAKE3 哈希。"""
+    hasher = blake3.blake3()
+    with open(path, "rb") as f:
+        while chunk := f.read(1 << 20):
+            hasher.update(chunk)
+    return hasher.hexdigest()
+
+
+def blake3_range(path: Path, offset: int, length: int) -> str:
+    """计算文件中 [offset, offset + length) 字节的 BLAKE3。"""
+    hasher = blake3.blake3()
+    with open(path, "rb") as f:
+        f.seek(offset)
+        while length > 0:
+            chunk = f.read(min(length, 1 << 20))
+            if not chunk:
+                break
+            hasher.update(chunk)
+            length -= len(chunk)
+    return hasher.hexdigest()
+
+
+def fsync_path(path: Path) -> None:
+    """fsync 文件或目录。对目录 fsync 使其中刚完成的创建 / 重命名持久化。
+
+    Windows 不能以只读方式打开目录，目录跳过。
+    """
+    if os.name == "nt" and path.is_dir():
+        return
+    fd = os.open(path, os.O_RDONLY)
+    try:
+        os.fsync(fd)
+    finally:
+        os.close(fd)
+
+
+def _is_relative_filename(filename: str) -> bool:
+    """filename 是否为不越出 manifest 目录的相对路径 (同时按 / 与 \\ 分隔判断)。"""
+    posix = PurePosixPath(filename.replace("\\", "/"))
+    return not (posix.is_absolute() or PureWindowsPath(filename).drive or ".." in posix.parts)
+
+
+def _is_count(value) -> bool:
+    """是否为非负整数 (JSON 中的 true/false 不算)。"""
+    return isinstance(value, int) and not isinstance(value, bool) and value >= 0
+
+
+@dataclass
+class LayerDigest:
+    """多层分片中单层数据所在的字节区间及其 hash。"""
+
+    offset: int
+    length: int
+    hash: str
+
+    def to_dict(self) -> dict:
+        return {"offset": self.offset, "length": self.length, "hash": f"blake3:{self.hash}"}
+
 
 @dataclass
 class Shard:
@@ -17,6 +92,9 @@ class Shard:
     bytes: int
     hash: str
     layer_range: tuple[int, int] | None = None
+    metadata: dict[str, str] | None = None
+    # 仅跨多层的 layer 分片：层号 → 该层字节区间的 hash，用于把损坏定位到层
+    layer_digests: dict[int, LayerDigest] | None = None
 
     def to_dict(self) -> dict:
         d = {
@@ -28,8 +106,110 @@ class Shard:
         }
         if self.layer_range is not None:
             d["layer_range"] = list(self.layer_range)
+        if self.layer_digests:
+            d["layer_digests"] = {
+                str(layer): digest.to_dict() for layer, digest in sorted(self.layer_digests.items())
+            }
+        if self.metadata:
+            d["metadata"] = dict(sorted(self.metadata.items()))
         return d
 
+    @classmethod
+    def from_dict(cls, d: dict) -> "Shard":
+        """从 manifest.json 中的分片条目还原，hash 去掉 blake3: 前缀。"""
+        layer_range = d.get("layer_range")
+        return cls(
+            id=d["id"],
+            kind=d["kind"],
+            filename=d["filename"],
+            bytes=d["bytes"],
+            hash=d["hash"].removeprefix("blake3:"),
+            layer_range=tuple(layer_range) if layer_range is not None else None,
+            metadata=d.get("metadata"),
+            layer_digests={
+                int(layer): LayerDigest(
+                    offset=entry["offset"],
+                    length=entry["length"],
+                    hash=entry["hash"].removeprefix("blake3:"),
+                )
+                for layer, entry in d["layer_digests"].items()
+            } if "layer_digests" in d else None,
+        )
+
+    def resolve(self, base: Path) -> Path:
+        """分片文件在 base (manifest 所在目录) 下的路径。
+
+        filename 不是相对路径时抛出 ValidationError，避免读取 base 之外的文件。
+        """
+        if not _is_relative_filename(self.filename):
+            raise ValidationError(f"分片 {self.id} 的 filename 不是相对路径: {self.filename}")
+        return base / self.filename
+
+    def verify_file(self, path: Path) -> None:
+        """校验已下载/已写入的分片文件。
+
+        先比较大小 (无需读文件)，不符时抛出 SizeMismatch；再比较 BLAKE3，不符时抛出
+        DigestMismatch。两者都是 IntegrityError 的子类；读文件失败时 OSError 原样抛出。
+        """
+        size = path.stat().st_size
+        if size != self.bytes:
+            raise SizeMismatch(f"分片 {self.id} 大小不符: {path} 为 {size} 字节，应为 {self.bytes}")
+        actual = blake3_file(path)
+        if actual != self.hash:
+            message = f"分片 {self.id} hash 不符: {path} 为 {actual}，应为 {self.hash}"
+            if self.layer_digests:
+                corrupt = [
+                    layer for layer, digest in sorted(self.layer_digests.items())
+                    if blake3_range(path, digest.offset, digest.length) != digest.hash
+                ]
+                message += f" (损坏的层: {', '.join(map(str, corrupt)) or '无，差异在层之外'})"
+            raise DigestMismatch(message)
+
+
+def download_hints(shards: list[Shard]) -> dict:
+    """根据分片统计给客户端的下载建议：并发数随分片数增长，上限为 6。"""
+    return {
+        "suggested_parallelism": max(1, min(MAX_SUGGESTED_PARALLELISM, len(shards))),
+        "total_bytes": sum(s.bytes for s in shards),
+    }
+
+
+def shard_dependencies(shards: list[Shard]) -> dict[str, list[str]]:
+    """每个 layer 分片前向计算依赖的分片 id：embed 分片加全部更低层的 layer 分片。
+
+    依赖已展开为传递闭包，调度器无需递归即可排序；只引用本 manifest 中存在的分片。
+    """
+    base = [s.id for s in shards if s.kind == "embed"]
+    layers = sorted(
+        (s for s in shards if s.kind == "layer" and s.layer_range is not None),
+        key=lambda s: s.layer_range[0],
+    )
+    return {
+        shard.id: base + [s.id for s in layers if s.layer_range[1] < shard.layer_range[0]]
+        for shard in layers
+    }
+
+
+def oversized_base_shards(shards: list[Shard], ratio: float) -> list[Shard]:
+    """字节数超过 layer 分片平均值 ratio 倍的 embed / lm_head 分片。
+
+    base 分片必须在首个 token 之前下载完，过大时会拖慢首屏；没有 layer 分片时返回空。
+    """
+    layer_bytes = [s.bytes for s in shards if s.kind == "layer"]
+    if not layer_bytes:
+        return []
+    average = sum(layer_bytes) / len(layer_bytes)
+    return [s for s in shards if s.kind != "layer" and s.bytes > ratio * average]
+
+
+def covered_layers(shards: list[Shard]) -> list[int]:
+    """layer 分片覆盖的全部层号 (升序)。"""
+    layers: set[int] = set()
+    for shard in shards:
+        if shard.layer_range is not None:
+            layers.update(range(shard.layer_range[0], shard.layer_range[1] + 1))
+    return sorted(layers)
+
 
 @dataclass
 class ModelManifest:
@@ -40,21 +220,185 @@ class ModelManifest:
     total_layers: int
     shards: list[Shard]
     version: str = "0.2"
+    tie_word_embeddings: bool = False
+    partial: bool = False
+    included_layers: list[int] | None = None
+    included_base: list[str] | None = None
+    shuffle_seed: int | None = None
+    created_by: str | None = None
+    notes: str | None = None
+
+    def total_bytes(self) -> int:
+        """全部分片的字节数之和。"""
+        return sum(s.bytes for s in self.shards)
+
+    def layer_count(self) -> int:
+        """layer 分片覆盖到的层数，即最大 layer_range 结束层号 + 1；没有 layer 分片时为 0。"""
+        return max((s.layer_range[1] + 1 for s in self.shards if s.layer_range is not None), default=0)
+
+    def shard_for_layer(self, layer: int) -> Shard | None:
+        """包含指定层的 layer 分片；该层不在任何分片中时返回 None。"""
+        for shard in self.shards:
+            if shard.layer_range is not None and shard.layer_range[0] <= layer <= shard.layer_range[1]:
+                return shard
+        return None
+
+    def manifest_digest(self) -> str:
+        """整套分片的总 hash，一次比较即可确认分片集合完整且正确。
+
+        对按 id 排序的 `<id>=<hex>` 行 (以 \\n 连接) 计算 BLAKE3。
+        """
+        lines = "\n".join(f"{s.id}={s.hash}" for s in sorted(self.shards, key=lambda s: s.id))
+        return blake3.blake3(lines.encode()).hexdigest()
 
     def to_dict(self) -> dict:
-        return {
+        dependencies = shard_dependencies(self.shards)
+        shards = []
+        for shard in self.shards:
+            entry = shard.to_dict()
+            if shard.id in dependencies:
+                entry["depends_on"] = dependencies[shard.id]
+            shards.append(entry)
+        d = {
             "version": self.version,
             "model_id": self.model_id,
             "variant": self.variant,
             "framework": self.framework,
             "dtype": self.dtype,
             "total_layers": self.total_layers,
-            "shards": [s.to_dict() for s in self.shards],
+            "shards": shards,
+            "download_hints": download_hints(self.shards),
+            "manifest_digest": f"blake3:{self.manifest_digest()}",
         }
+        if self.tie_word_embeddings:
+            d["tie_word_embeddings"] = True
+        if self.partial:
+            d["partial"] = True
+        if self.included_layers is not None:
+            d["included_layers"] = self.included_layers
+        if self.included_base is not None:
+            d["included_base"] = self.included_base
+        if self.shuffle_seed is not None:
+            d["shuffle_seed"] = self.shuffle_seed
+        if self.created_by:
+            d["created_by"] = self.created_by
+        if self.notes:
+            d["notes"] = self.notes
+        return d
+
+    @classmethod
+    def from_dict(cls, d: dict) -> "ModelManifest":
+        """从 manifest.json 还原；depends_on、download_hints、manifest_digest 为派生字段，不读取。"""
+        return cls(
+            model_id=d["model_id"],
+            variant=d["variant"],
+            framework=d["framework"],
+            dtype=d["dtype"],
+            total_layers=d["total_layers"],
+            shards=[Shard.from_dict(s) for s in d["shards"]],
+            version=d["version"],
+            tie_word_embeddings=d.get("tie_word_embeddings", False),
+            partial=d.get("partial", False),
+            included_layers=d.get("included_layers"),
+            included_base=d.get("included_base"),
+            shuffle_seed=d.get("shuffle_seed"),
+            created_by=d.get("created_by"),
+            notes=d.get("notes"),
+        )
+
+    def validate(self) -> None:
+        """检查 manifest 内部一致性，不合法时抛出 ValidationError。
+
+        - total_layers、bytes 为非负整数，layer_range 为两个整数
+        - layer 分片必须有 layer_range 且 start <= end
+        - embed / lm_head 分片不应带 layer_range
+        - filename 必须是相对于 manifest 所在目录的路径 (不能是绝对路径或含 ..)，
+          保证整套分片可以整体移动
+        - hash 与 layer_digests 中的 hash 必须是 64 位小写 hex (BLAKE3-256)
+        - 分片 id 与 filename 不能重复
+        - layer 分片的 layer_range 不能互相重叠，也不能超出 total_layers
+        - shuffle_seed 在 0 到 MAX_SHUFFLE_SEED 之间 (JS 可精确表示)
+        """
+        if self.shuffle_seed is not None and not 0 <= self.shuffle_seed <= MAX_SHUFFLE_SEED:
+            raise ValidationError(f"shuffle_seed 超出 0 到 2^53-1: {self.shuffle_seed}")
+        if not _is_count(self.total_layers):
+            raise ValidationError(f"total_layers 不是非负整数: {self.total_layers!r}")
+
+        for field_name in ("id", "filename"):
+            seen: set[str] = set()
+            for shard in self.shards:
+                value = getattr(shard, field_name)
+                if value in seen:
+                    raise ValidationError(f"分片 {field_name} 重复: {value}")
+                seen.add(value)
+
+        for shard in self.shards:
+            if not _is_count(shard.bytes):
+                raise ValidationError(f"分片 {shard.id} 的 bytes 不是非负整数: {shard.bytes!r}")
+            if shard.layer_range is not None and (
+                len(shard.layer_range) != 2 or not all(_is_count(i) for i in shard.layer_range)
+            ):
+                raise ValidationError(
+                    f"分片 {shard.id} 的 layer_range 应为两个非负整数: {list(shard.layer_range)}"
+                )
+            if not isinstance(shard.hash, str) or not BLAKE3_HEX_RE.fullmatch(shard.hash):
+                raise ValidationError(f"分片 {shard.id} 的 hash 不是 64 位 hex: {shard.hash!r}")
+            for layer, digest in (shard.layer_digests or {}).items():
+                if not isinstance(digest.hash, str) or not BLAKE3_HEX_RE.fullmatch(digest.hash):
+                    raise ValidationError(
+                        f"分片 {shard.id} 第 {layer} 层的 hash 不是 64 位 hex: {digest.hash!r}"
+                    )
+            if not isinstance(shard.filename, str) or not _is_relative_filename(shard.filename):
+                raise ValidationError(f"分片 {shard.id} 的 filename 不是相对路径: {shard.filename}")
+            if shard.kind == "layer":
+                if shard.layer_range is None:
+                    raise ValidationError(f"layer 分片 {shard.id} 缺少 layer_range")
+                start, end = shard.layer_range
+                if start > end:
+                    raise ValidationError(
+                        f"分片 {shard.id} 的 layer_range 颠倒: [{start}, {end}]"
+                    )
+                if start < 0 or end >= self.total_layers:
+                    raise ValidationError(
+                        f"分片 {shard.id} 的 layer_range [{start}, {end}] 超出 total_layers {self.total_layers}"
+                    )
+            elif shard.layer_range is not None:
+                raise ValidationError(
+                    f"{shard.kind} 分片 {shard.id} 不应有 layer_range: {list(shard.layer_range)}"
+                )
+
+        layer_shards = sorted(
+            (s for s in self.shards if s.layer_range is not None), key=lambda s: s.layer_range
+        )
+        for prev, shard in zip(layer_shards, layer_shards[1:]):
+            if shard.layer_range[0] <= prev.layer_range[1]:
+                raise ValidationError(
+                    f"分片 {prev.id} 与 {shard.id} 的 layer_range 重叠: "
+                    f"{list(prev.layer_range)} / {list(shard.layer_range)}"
+                )
+
+    def write(
+        self, output_dir: Path, path: Path | None = None, compact: bool = False, fsync: bool = False,
+    ) -> Path:
+        """校验后写入 manifest；path 为空时写到 output_dir/manifest.json。
 
-    def write(self, output_dir: Path) -> Path:
-        path = output_dir / "manifest.json"
+        filename 始终相对于分片所在的 output_dir，与 manifest 写到哪里无关。
+        compact=True 时写成无缩进、无多余空格的单行 JSON (分片很多时体积明显更小)。
+        fsync=True 时把文件及其所在目录刷到磁盘。
+        """
+        self.validate()
+        if path is None:
+            path = output_dir / "manifest.json"
+        path.parent.mkdir(parents=True, exist_ok=True)
         with open(path, "w") as f:
-            json.dump(self.to_dict(), f, indent=2)
+            if compact:
+                json.dump(self.to_dict(), f, separators=(",", ":"))
+            else:
+                json.dump(self.to_dict(), f, indent=2)
+            if fsync:
+                f.flush()
+                os.fsync(f.fileno())
+        if fsync:
+            fsync_path(path.parent)
         print(f"已写入 {path}")
         return path
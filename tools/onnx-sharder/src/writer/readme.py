@@ -0,0 +1,43 @@
+"""--emit-readme: 由 ModelManifest 生成 CHUNKS.md 分片说明，供模型卡引用。
+
+表格内容全部取自 manifest，与同一次写出的 manifest.json 保持一致。
+"""
+
+from pathlib import Path
+
+from .manifest import ModelManifest
+
+
+def _layers_cell(layer_range: tuple[int, int] | None) -> str:
+    if layer_range is None:
+        return "-"
+    start, end = layer_range
+    return str(start) if start == end else f"{start}-{end}"
+
+
+def render_chunks_markdown(manifest: ModelManifest) -> str:
+    """渲染分片表：每个分片一行，列为 id / kind / 层 / 大小 / 文件 / hash。"""
+    lines = [
+        f"# {manifest.model_id} ({manifest.variant}, {manifest.dtype})",
+        "",
+        f"共 {len(manifest.shards)} 个分片，{manifest.total_layers} 层，"
+        f"合计 {manifest.total_bytes() / 1024 / 1024:.1f} MB。",
+        "",
+        "| id | kind | 层 | 大小 (MB) | 文件 | hash |",
+        "|----|------|----|-----------|------|------|",
+    ]
+    for shard in manifest.shards:
+        lines.append(
+            f"| {shard.id} | {shard.kind} | {_layers_cell(shard.layer_range)} "
+            f"| {shard.bytes / 1024 / 1024:.2f} | `{shard.filename}` | `blake3:{shard.hash}` |"
+        )
+    lines += ["", f"manifest_digest: `blake3:{manifest.manifest_digest()}`", ""]
+    return "\n".join(lines)
+
+
+def write_chunks_readme(manifest: ModelManifest, output_dir: Path) -> Path:
+    """写入 output_dir/CHUNKS.md 并返回路径。"""
+    path = output_dir / "CHUNKS.md"
+    path.write_text(render_chunks_markdown(manifest))
+    print(f"已写入 {path}")
+    return path
@@ -0,0 +1,129 @@
+"""从已有的分片输出目录重新生成 manifest 分片列表。
+
+适用于分片文件仍在、但 manifest.json 丢失或过期的情况。只读取精简
+model.onnx 的图结构和 initializer 的 external data 引用，不加载权重:
+
+1. 按 classify 规则判定每个 initializer 属于 embed/norm/lm_head/layer(N)
+2. 按 external data 的 location 把 initializer 归到分片文件
+3. 由文件内 tensor 的分类推断 kind 与 layer_range，重新计算 bytes、hash
+   和多层分片的逐层 hash
+"""
+
+from pathlib import Path
+
+import onnx
+from onnx import TensorProto
+
+from ..errors import InputError
+from ..parser.classify import BASE_GROUPS, ClassifyResult, base_group_tensors
+from .layer_digest import assign_layer_digests
+from .manifest import Shard, ShardKind
+from .shard_writer import EXCLUDED_LAYERS_FILENAME, blake3_file, layer_shard_id
+
+
+def _tensor_location(tensor: TensorProto) -> str | None:
+    """返回 external data 的文件名，内联 tensor 返回 None。"""
+    if tensor.data_location != TensorProto.EXTERNAL:
+        return None
+    for entry in tensor.external_data:
+        if entry.key == "location":
+            return entry.value
+    return None
+
+
+def rebuild_shards(
+    model: onnx.ModelProto,
+    classify_result: ClassifyResult,
+    output_dir: Path,
+    base_include: list[str] | None = None,
+) -> tuple[list[Shard], bool]:
+    """根据 model.onnx 中的 external data 引用重建 Shard 列表。
+
+    Args:
+        model: 以 load_external_data=False 加载的精简 model.onnx
+        classify_result: 对该模型 initializer 的分类结果
+        output_dir: 分片文件所在目录
+        base_include: 分片时的 --base-include，其余 base 分组写在 manifest 之外的文件中
+            (见 plan_excluded_base)，不算分片也不算部分输出
+
+    Returns:
+        (按 embed → layer → lm_head 排序的 Shard 列表, 是否为部分输出)
+        部分输出指仍有带数据的 initializer 内联在 model.onnx 中
+        （如 --limit-chunks 的产物），或有层写在 model.onnx_data_layers_excluded 中
+        （--only-layers 的产物，该文件不算分片）。
+    """
+    excluded_base = {
+        tensor.name
+        for group in BASE_GROUPS
+        if base_include is not None and group not in base_include
+        for tensor in base_group_tensors(classify_result, group)
+    }
+    kinds: dict[str, tuple[str, int | None]] = {}
+    for tensor in classify_result.embed + classify_result.norm:
+        kinds[tensor.name] = ("embed", None)
+    for tensor in classify_result.lm_head:
+        kinds[tensor.name] = ("lm_head", None)
+    for layer_idx, tensors in classify_result.layers.items():
+        for tensor in tensors:
+            kinds[tensor.name] = ("layer", layer_idx)
+
+    files: dict[str, list[tuple[str, int | None]]] = {}
+    partial = False
+    for tensor in model.graph.initializer:
+        if tensor.name in excluded_base:
+            continue
+        location = _tensor_location(tensor)
+        if location is None:
+            partial = partial or bool(tensor.raw_data)
+            continue
+        if location.startswith(EXCLUDED_LAYERS_FILENAME):
+            partial = True
+            continue
+        files.setdefault(location, []).append(kinds[tensor.name])
+
+    if not files:
+        raise InputError(f"model.onnx 未引用任何 external data 文件 ({output_dir})")
+
+    # 旧模式 (--no-split-base) 没有独立的 embed / lm_head 文件 (--base-include 可能只留其一)；
+    # 文件名可能带 --shard-extension 后缀
+    split_base = any(
+        name.startswith(("model.onnx_data_embed", "model.onnx_data_lm_head")) for name in files
+    )
+
+    shards: list[Shard] = []
+    for filename, members in files.items():
+        data_path = output_dir / filename
+        if not data_path.is_file():
+            raise InputError(f"model.onnx 引用的分片文件不存在: {data_path}")
+
+        layer_idxs = [idx for kind, idx in members if kind == "layer"]
+        kind: ShardKind
+        layer_range: tuple[int, int] | None = None
+        if layer_idxs:
+            kind = "layer"
+            layer_range = (min(layer_idxs), max(layer_idxs))
+            shard_id = layer_shard_id(*layer_range, split_base=split_base)
+        elif split_base and all(k == "lm_head" for k, _ in members):
+            kind = shard_id = "lm_head"
+        else:
+            kind = shard_id = "embed"
+
+        size = data_path.stat().st_size
+        try:
+            file_hash = blake3_file(data_path)
+        except OSError as e:
+            raise InputError(f"无法读取分片文件 {data_path}: {e}") from e
+        shards.append(Shard(
+            id=shard_id,
+            kind=kind,
+            filename=filename,
+            bytes=size,
+            hash=file_hash,
+            layer_range=layer_range,
+        ))
+        print(f"  {filename}: {shard_id} ({size / 1024 / 1024:.1f} MB)")
+
+    order = {"embed": 0, "layer": 1, "lm_head": 2}
+    shards.sort(key=lambda s: (order[s.kind], s.layer_range or (0, 0)))
+    assign_layer_digests(classify_result, shards, output_dir)
+    return shards, partial
@@ -15,40 +15,212 @@ split_base=False 输出结构 (兼容旧模式):
   ...
 """
 
+import json
+import os
+import random
+import re
+import shutil
+import sys
+import threading
+import time
+from dataclasses import dataclass
 from pathlib import Path
 
-import blake3
 import onnx
 from onnx import TensorProto
 
-from ..parser.classify import ClassifyResult
-from .manifest import Shard, ShardKind
+from .. import progress
+from ..errors import Cancelled, IntegrityError, OutputError, ValidationError
+from ..parser.classify import BASE_GROUPS, ClassifyResult, base_group_tensors
+from ..parser.dtypes import stored_data_bytes, tensor_nbytes
+from .manifest import ModelManifest, Shard, ShardKind, blake3_file, fsync_path
+
+# --embed-manifest 写入 model.onnx metadata_props 时使用的 key
+MANIFEST_METADATA_KEY = "pb_ai.manifest"
+
+# --base-include 未列出的 base 分组写入的 external data 文件 (不列入 manifest)
+EXCLUDED_BASE_FILENAME = "model.onnx_data_base_excluded"
+# --only-layers 未选中的层写入的 external data 文件 (不列入 manifest)
+EXCLUDED_LAYERS_FILENAME = "model.onnx_data_layers_excluded"
 
 
 def _tensor_raw_bytes(tensor: TensorProto) -> bytes:
-    """提取 tensor 的原始字节数据。"""
+    """提取 tensor 的原始字节数据，长度与 tensor_nbytes 一致。"""
     if tensor.raw_data:
         return tensor.raw_data
-    arr = onnx.numpy_helper.to_array(tensor)
-    return arr.tobytes()
+    return stored_data_bytes(tensor)
+
+
+@dataclass
+class ShardPlan:
+    """一个待写入分片：tensor 分组及其在 manifest 中的描述。"""
+
+    shard_id: str
+    kind: ShardKind
+    filename: str
+    label: str
+    tensors: list[TensorProto]
+    layer_range: tuple[int, int] | None = None
+
+
+def estimate_output_bytes(plans: list[ShardPlan], align: int = 1) -> int:
+    """估算所有分片写出的总字节数（各分片 tensor 数据之和，加上对齐补零的上限）。"""
+    return sum(tensor_nbytes(t) + align - 1 for plan in plans for t in plan.tensors)
+
+
+def padding_bytes(plans: list[ShardPlan], shards: list[Shard]) -> int:
+    """已写出分片中 --align 对齐补零的总字节数，即写出字节数减去 tensor 数据之和。
+
+    shards 与 plans 一一对应 (write_shards 的返回值)；align 为 1 时恒为 0。
+    """
+    return sum(s.bytes for s in shards) - estimate_output_bytes(plans)
+
+
+def check_disk_space(plans: list[ShardPlan], output_dir: Path, align: int = 1) -> None:
+    """写入前检查输出目录所在文件系统的剩余空间，不足时直接报错。
+
+    避免写到一半才因 ENOSPC 失败，留下残缺的分片目录。
+    model.onnx 图定义不计入估算（通常仅数 MB）。
+    """
+    required = estimate_output_bytes(plans, align)
+    free = shutil.disk_usage(output_dir).free
+    print(f"  预计写入 {required / 1024 / 1024:.1f} MB，可用 {free / 1024 / 1024:.1f} MB")
+    if required > free:
+        raise OutputError(
+            f"输出目录磁盘空间不足: 需要 {required / 1024 / 1024:.1f} MB，"
+            f"可用 {free / 1024 / 1024:.1f} MB ({output_dir})"
+        )
 
 
-def _write_data_file(
+def layer_chunk_ranges(total_layers: int, layers_per_chunk: int) -> list[tuple[int, int]]:
+    """将 [0, total_layers) 按 layers_per_chunk 切分为闭区间 [start, end] 列表。
+
+    最后一组可能不足 layers_per_chunk 层。例如 (5, 2) -> [(0, 1), (2, 3), (4, 4)]。
+    """
+    if layers_per_chunk < 1:
+        raise ValueError(f"layers_per_chunk 必须 >= 1，当前为 {layers_per_chunk}")
+    return [
+        (start, min(start + layers_per_chunk, total_layers) - 1)
+        for start in range(0, total_layers, layers_per_chunk)
+    ]
+
+
+def balanced_layer_ranges(total_layers: int, chunk_count: int) -> list[tuple[int, int]]:
+    """将 [0, total_layers) 尽量均匀地切分为 chunk_count 个闭区间，余数分给前面的分片。
+
+    例如 (10, 3) -> [(0, 3), (4, 6), (7, 9)]。chunk_count 超过层数时每层一个分片。
+    """
+    if chunk_count < 1:
+        raise ValueError(f"chunk_count 必须 >= 1，当前为 {chunk_count}")
+    chunk_count = min(chunk_count, total_layers)
+    ranges: list[tuple[int, int]] = []
+    start = 0
+    for i in range(chunk_count):
+        size = total_layers // chunk_count + (1 if i < total_layers % chunk_count else 0)
+        ranges.append((start, start + size - 1))
+        start += size
+    return ranges
+
+
+def selected_layer_ranges(
+    layers: list[int], layers_per_chunk: int,
+) -> list[tuple[int, int]]:
+    """将离散层号按连续段切分，每段再按 layers_per_chunk 切分为闭区间。
+
+    例如 ([0, 5, 6, 7, 31], 2) -> [(0, 0), (5, 6), (7, 7), (31, 31)]。
+    """
+    ranges: list[tuple[int, int]] = []
+    run_start = None
+    ordered = sorted(set(layers))
+    for i, layer in enumerate(ordered):
+        if run_start is None:
+            run_start = layer
+        if i + 1 == len(ordered) or ordered[i + 1] != layer + 1:
+            for start, end in layer_chunk_ranges(layer - run_start + 1, layers_per_chunk):
+                ranges.append((run_start + start, run_start + end))
+            run_start = None
+    return ranges
+
+
+def tensor_count_ranges(
+    layer_counts: list[tuple[int, int]], max_tensors: int,
+) -> list[tuple[int, int]]:
+    """按 tensor 数上限把 (层号, tensor 数) 序列贪心打包为闭区间。
+
+    同一层的 tensor 不拆分，单层超过上限时独占一个分片并打印警告；
+    层号不连续处总是断开，保证 layer_range 覆盖的都是实际打包的层。
+    """
+    ranges: list[tuple[int, int]] = []
+    start = prev = None
+    count = 0
+    for layer, n in layer_counts:
+        if start is not None and (layer != prev + 1 or count + n > max_tensors):
+            ranges.append((start, prev))
+            start = None
+        if start is None:
+            start, count = layer, 0
+        if n > max_tensors:
+            print(f"  警告: layer {layer} 有 {n} 个 tensor，超过上限 {max_tensors}，单独成片", file=sys.stderr)
+        count += n
+        prev = layer
+    if start is not None:
+        ranges.append((start, prev))
+    return ranges
+
+
+def byte_limited_ranges(
+    ranges: list[tuple[int, int]], layer_bytes: dict[int, int], max_bytes: int,
+) -> list[tuple[int, int]]:
+    """把超过 max_bytes 的层区间贪心拆小，使每段的字节数不超过 max_bytes。
+
+    layer_bytes 为每层写出后最多占用的字节数 (含对齐补零的上限，见 plan_shards)。
+    层不可拆分：单层超过上限时抛出 ValidationError。
+    """
+    result: list[tuple[int, int]] = []
+    for start, end in ranges:
+        seg_start, seg_bytes = start, 0
+        for layer in range(start, end + 1):
+            size = layer_bytes.get(layer, 0)
+            if size > max_bytes:
+                raise ValidationError(
+                    f"layer {layer} 最多 {size} 字节，超过单文件上限 {max_bytes} 字节，层不可拆分"
+                )
+            if seg_bytes + size > max_bytes:
+                result.append((seg_start, layer - 1))
+                seg_start, seg_bytes = layer, 0
+            seg_bytes += size
+        result.append((seg_start, end))
+    return result
+
+
+def write_data_file(
     tensors: list[TensorProto],
     data_path: Path,
+    align: int = 1,
+    fsync: bool = False,
+    location: str | None = None,
 ) -> int:
     """将一组 tensor 写入单个 external data 文件。
 
-    同时更新每个 tensor 的 external_data 引用（文件名、偏移量、长度）。
+    同时更新每个 tensor 的 external_data 引用（文件名、偏移量、长度）；
+    location 为引用中记录的文件名，默认为 data_path 的文件名 (写临时文件时传入最终文件名)。
+    align > 1 时每个 tensor 的偏移量补零对齐到 align 的整数倍，
+    供 mmap 加载、要求对齐的运行时使用；补零计入文件大小。
+    fsync 为 True 时关闭前将文件刷到磁盘，保证 manifest 引用它之前数据已持久化。
 
     Returns:
         写入的总字节数
     """
     offset = 0
-    data_filename = data_path.name
+    data_filename = location or data_path.name
 
     with open(data_path, "wb") as f:
         for tensor in tensors:
+            padding = -offset % align
+            if padding:
+                f.write(b"\0" * padding)
+                offset += padding
+
             raw = _tensor_raw_bytes(tensor)
             length = len(raw)
             f.write(raw)
@@ -69,157 +241,451 @@ def _write_data_file(
 
             offset += length
 
+        if fsync:
+            f.flush()
+            os.fsync(f.fileno())
+
     return offset
 
 
-def _blake3_file(path: Path) -> str:
-    """计算文件的 BLAKE3 哈希。"""
-    hasher = blake3.blake3()
-    with open(path, "rb") as f:
-        while chunk := f.read(1 << 20):
-            hasher.update(chunk)
-    return hasher.hexdigest()
+def planned_file_bytes(tensors: list[TensorProto], align: int = 1) -> int:
+    """按 tensor_nbytes 与对齐规则推算的文件大小 (tensor 数据加实际的对齐补零)，写入前计算。"""
+    offset = 0
+    for tensor in tensors:
+        offset += -offset % align + tensor_nbytes(tensor)
+    return offset
 
 
-def _write_shard(
-    tensors: list[TensorProto],
-    shard_id: str,
-    kind: ShardKind,
-    data_path: Path,
-    label: str,
-    layer_range: tuple[int, int] | None = None,
-) -> Shard | None:
-    """写入一个分片并返回 Shard，无 tensor 时返回 None。"""
-    if not tensors:
-        return None
+def _check_written_size(data_path: Path, total_bytes: int, planned_bytes: int) -> None:
+    """写入后核对文件大小：与写入字节数一致，且恰好等于规划的大小 (planned_file_bytes)。
+
+    便宜的兜底检查，防止序列化或文件系统静默丢数据，也防止某个 tensor 写出的字节数
+    与规划不符 (如被展开成更宽的类型)。
+    """
+    on_disk = data_path.stat().st_size
+    if on_disk != total_bytes or total_bytes != planned_bytes:
+        raise IntegrityError(
+            f"{data_path.name} 写入大小异常: 文件 {on_disk} 字节，写入 {total_bytes} 字节，"
+            f"应为 {planned_bytes} 字节"
+        )
+
+
+def temp_path(path: Path) -> Path:
+    """path 对应的临时文件: 同目录下的 .<文件名>.<pid>.tmp。
+
+    文件名唯一对应一个分片，pid 区分同时写同一目录的多个进程，
+    命名确定，便于 --clean-temp 识别并清理崩溃后残留的文件。
+    """
+    return path.with_name(f".{path.name}.{os.getpid()}.tmp")
+
+
+TEMP_FILE_RE = re.compile(r"^\..+\.(\d+)\.tmp$")
+
 
-    total_bytes = _write_data_file(tensors, data_path)
-    file_hash = _blake3_file(data_path)
-    print(f"  {data_path.name}: {label} ({total_bytes / 1024 / 1024:.1f} MB)")
+def _pid_alive(pid: int) -> bool:
+    """pid 对应的进程是否仍在运行。Windows 上无法用信号 0 探测，一律视为仍在运行。"""
+    if pid == os.getpid() or os.name == "nt":
+        return True
+    try:
+        os.kill(pid, 0)
+    except ProcessLookupError:
+        return False
+    except PermissionError:
+        # 进程存在，但属于其他用户
+        return True
+    return True
+
+
+def clean_temp_files(output_dir: Path) -> list[Path]:
+    """删除 output_dir 中符合 temp_path 命名的残留临时文件，返回被删除的路径。
+
+    只删除写入进程已不存在的文件；仍在运行的进程 (含本进程) 的临时文件保留，
+    不会破坏同时写同一目录的另一次运行。
+    """
+    if not output_dir.is_dir():
+        return []
+    removed = []
+    for path in output_dir.iterdir():
+        m = TEMP_FILE_RE.match(path.name)
+        if m and path.is_file() and not _pid_alive(int(m.group(1))):
+            path.unlink()
+            removed.append(path)
+    return removed
+
+
+def _write_shard(plan: ShardPlan, output_dir: Path, align: int = 1, fsync: bool = False) -> Shard:
+    """写入一个分片并返回 Shard。
+
+    先写入临时文件，大小校验和 hash 完成后再原子重命名为最终文件名，
+    中途中断不会留下同名的残缺分片。
+    """
+    data_path = output_dir / plan.filename
+    tmp_path = temp_path(data_path)
+    progress.emit("shard_started", id=plan.shard_id, filename=plan.filename)
+    start = time.perf_counter()
+    planned_bytes = planned_file_bytes(plan.tensors, align)
+    try:
+        total_bytes = write_data_file(
+            plan.tensors, tmp_path, align, fsync, location=plan.filename,
+        )
+        _check_written_size(tmp_path, total_bytes, planned_bytes)
+        file_hash = blake3_file(tmp_path)
+        os.replace(tmp_path, data_path)
+        if fsync:
+            # 重命名只有在目录项落盘后才持久
+            fsync_path(output_dir)
+    except BaseException:
+        # Ctrl-C 或写入失败时删除临时文件，避免留下残缺数据
+        tmp_path.unlink(missing_ok=True)
+        raise
+    elapsed_ms = (time.perf_counter() - start) * 1000
+    print(f"  {data_path.name}: {plan.label} ({total_bytes / 1024 / 1024:.1f} MB)")
+    progress.emit("shard_done", id=plan.shard_id, bytes=total_bytes, ms=round(elapsed_ms, 1))
 
     return Shard(
-        id=shard_id,
-        kind=kind,
+        id=plan.shard_id,
+        kind=plan.kind,
         filename=data_path.name,
         bytes=total_bytes,
         hash=file_hash,
-        layer_range=layer_range,
+        layer_range=plan.layer_range,
     )
 
 
-def write_shards(
-    model: onnx.ModelProto,
+def _save_model(model: onnx.ModelProto, output_dir: Path, fsync: bool = False) -> Path:
+    """保存 output_dir/model.onnx，与分片相同先写临时文件再原子重命名。
+
+    中途中断 (含 Ctrl-C) 时删除临时文件，不会留下被截断的 model.onnx。
+    """
+    model_path = output_dir / "model.onnx"
+    tmp_path = temp_path(model_path)
+    try:
+        onnx.save(model, str(tmp_path))
+        if fsync:
+            fsync_path(tmp_path)
+        os.replace(tmp_path, model_path)
+        if fsync:
+            fsync_path(output_dir)
+    except BaseException:
+        tmp_path.unlink(missing_ok=True)
+        raise
+    return model_path
+
+
+def layer_shard_id(start: int, end: int, split_base: bool = True) -> str:
+    """layer 分片 ID。split_base 模式下单层分片为 layer_N，其余为 layers_A-B。"""
+    if split_base and start == end:
+        return f"layer_{start}"
+    return f"layers_{start}-{end}"
+
+
+def _with_data(tensors: list[TensorProto]) -> list[TensorProto]:
+    """过滤掉 0 字节 tensor (某一维为 0)。
+
+    这类 tensor 保留为 model.onnx 内联数据：写成长度为 0 的 external data
+    条目没有意义，部分 onnxruntime 版本还会拒绝加载。
+    """
+    return [t for t in tensors if tensor_nbytes(t) > 0]
+
+
+def _layer_group(classify_result: ClassifyResult, start: int, end: int) -> list[TensorProto]:
+    """收集 [start, end] 层范围内的全部非空 tensor。"""
+    tensors: list[TensorProto] = []
+    for layer_idx in range(start, end + 1):
+        tensors.extend(classify_result.layers.get(layer_idx, []))
+    return _with_data(tensors)
+
+
+def plan_shards(
     classify_result: ClassifyResult,
-    output_dir: Path,
     layers_per_chunk: int,
     split_base: bool = True,
-) -> list[Shard]:
-    """将模型写为精简 ONNX + 编号 external data 文件。
+    extension: str | None = None,
+    only_layers: list[int] | None = None,
+    tensors_per_chunk: int | None = None,
+    target_chunk_count: int | None = None,
+    max_file_bytes: int | None = None,
+    base_include: list[str] | None = None,
+    shuffle_seed: int | None = None,
+    align: int = 1,
+) -> list[ShardPlan]:
+    """按分类结果规划分片，不写任何文件。
+
+    没有非空 tensor 的分组会被跳过；layer 分组被跳过时打印警告。
 
     Args:
-        model: 加载的 ONNX 模型
         classify_result: 分类结果
-        output_dir: 输出目录
         layers_per_chunk: 每个分片的层数
         split_base: 是否将 base 拆分为 embed/lm_head 独立分片
+        extension: 分片文件扩展名 (不含点)，为空时沿用 model.onnx_data_<suffix>
+        only_layers: 只为这些层生成分片 (每个连续段单独切分)，其余层
+            由 plan_excluded_layers 规划到 manifest 之外的文件
+        tensors_per_chunk: 按 tensor 数而非层数打包分片，设置后忽略 layers_per_chunk
+        target_chunk_count: 把全部层均匀切分为这么多个 layer 分片 (不含 base)，
+            设置后忽略 layers_per_chunk；不能与 only_layers 同时使用
+        max_file_bytes: 单个分片文件的大小上限，按 tensor 数据加上每个 tensor 最多 align - 1
+            字节的对齐补零估算；超过时把多层分片拆小，单层或 base 分片超过上限时抛出 ValidationError
+        base_include: 只把这些 base 分组 (BASE_GROUPS 中的名称) 写入分片，其余 base 权重
+            由 plan_excluded_base 规划到 manifest 之外的文件；为空时写出全部 base 权重
+        shuffle_seed: 设置时按该种子打乱每个分片内的 tensor 顺序 (见 shuffle_tensors)
+        align: 写入时的 tensor 偏移对齐字节数，只用于 max_file_bytes 的估算
 
     Returns:
-        Shard 列表（用于生成 manifest.json）
+        按写入顺序排列的 ShardPlan 列表
     """
-    output_dir.mkdir(parents=True, exist_ok=True)
-    shards: list[Shard] = []
-    data_idx = 0
+
+    def data_filename(suffix: str | int) -> str:
+        name = f"model.onnx_data_{suffix}"
+        return f"{name}.{extension}" if extension else name
+
+    def base(*groups: str) -> list[TensorProto]:
+        tensors: list[TensorProto] = []
+        for group in groups:
+            if base_include is None or group in base_include:
+                tensors.extend(base_group_tensors(classify_result, group))
+        return _with_data(tensors)
+
+    plans: list[ShardPlan] = []
+    total_layers = classify_result.max_layer + 1
+    if total_layers == 0:
+        print("警告: 未找到任何层级 tensor", file=sys.stderr)
+    if tensors_per_chunk is not None:
+        layers = only_layers if only_layers is not None else range(total_layers)
+        layer_ranges = tensor_count_ranges(
+            [(layer, len(_layer_group(classify_result, layer, layer))) for layer in layers],
+            tensors_per_chunk,
+        )
+    elif target_chunk_count is not None:
+        layer_ranges = balanced_layer_ranges(total_layers, target_chunk_count)
+    elif only_layers is None:
+        layer_ranges = layer_chunk_ranges(total_layers, layers_per_chunk)
+    else:
+        layer_ranges = selected_layer_ranges(only_layers, layers_per_chunk)
+    if max_file_bytes is not None:
+        layer_bytes = {
+            layer: sum(tensor_nbytes(t) + align - 1 for t in _with_data(tensors))
+            for layer, tensors in classify_result.layers.items()
+        }
+        layer_ranges = byte_limited_ranges(layer_ranges, layer_bytes, max_file_bytes)
 
     if split_base:
         # --- embed 分片 (embed_tokens + norm) ---
-        embed_tensors = classify_result.embed + classify_result.norm
-        shard = _write_shard(
-            embed_tensors, "embed", "embed",
-            output_dir / "model.onnx_data_embed", "embed + norm",
-        )
-        if shard:
-            shards.append(shard)
+        embed_tensors = base("embed_tokens", "norm")
+        if embed_tensors:
+            plans.append(ShardPlan(
+                "embed", "embed", data_filename("embed"), "embed + norm", embed_tensors,
+            ))
 
         # --- layer 分片 ---
-        if classify_result.max_layer < 0:
-            print("警告: 未找到任何层级 tensor")
-        else:
-            total_layers = classify_result.max_layer + 1
-            for group_start in range(0, total_layers, layers_per_chunk):
-                group_end = min(group_start + layers_per_chunk, total_layers) - 1
-                data_path = output_dir / f"model.onnx_data_{group_start}"
-
-                group_tensors: list[TensorProto] = []
-                for layer_idx in range(group_start, group_end + 1):
-                    group_tensors.extend(classify_result.layers.get(layer_idx, []))
-
-                if not group_tensors:
-                    continue
-
-                if group_start == group_end:
-                    shard_id = f"layer_{group_start}"
-                    label = f"layer {group_start}"
-                else:
-                    shard_id = f"layers_{group_start}-{group_end}"
-                    label = f"layers {group_start}-{group_end}"
-
-                shard = _write_shard(
-                    group_tensors, shard_id, "layer", data_path, label,
-                    layer_range=(group_start, group_end),
-                )
-                if shard:
-                    shards.append(shard)
+        for group_start, group_end in layer_ranges:
+            if group_start == group_end:
+                label = f"layer {group_start}"
+            else:
+                label = f"layers {group_start}-{group_end}"
+
+            group_tensors = _layer_group(classify_result, group_start, group_end)
+            if not group_tensors:
+                print(f"  警告: {label} 没有非空 tensor，跳过该分片", file=sys.stderr)
+                continue
+
+            plans.append(ShardPlan(
+                layer_shard_id(group_start, group_end), "layer",
+                data_filename(group_start), label, group_tensors,
+                layer_range=(group_start, group_end),
+            ))
 
         # --- lm_head 分片 ---
-        shard = _write_shard(
-            classify_result.lm_head, "lm_head", "lm_head",
-            output_dir / "model.onnx_data_lm_head", "lm_head",
-        )
-        if shard:
-            shards.append(shard)
+        if classify_result.tied_lm_head:
+            print("  lm_head 与 embed 共享权重 (tie_word_embeddings)，不生成独立分片")
+        lm_head_tensors = base("lm_head")
+        if lm_head_tensors:
+            plans.append(ShardPlan(
+                "lm_head", "lm_head", data_filename("lm_head"), "lm_head", lm_head_tensors,
+            ))
 
     else:
         # --- 旧模式: 所有 base 权重合并为一个分片 ---
-        all_base = classify_result.embed + classify_result.norm + classify_result.lm_head
+        data_idx = 0
+        all_base = base("embed_tokens", "norm", "lm_head")
         if all_base:
-            shard = _write_shard(
-                all_base, "embed", "embed",
-                output_dir / f"model.onnx_data_{data_idx}", "base (embed + norm + lm_head)",
-            )
-            if shard:
-                shards.append(shard)
+            plans.append(ShardPlan(
+                "embed", "embed", data_filename(data_idx),
+                "base (embed + norm + lm_head)", all_base,
+            ))
             data_idx += 1
 
         # --- 按层分组 ---
-        if classify_result.max_layer < 0:
-            print("警告: 未找到任何层级 tensor")
-        else:
-            total_layers = classify_result.max_layer + 1
-            for group_start in range(0, total_layers, layers_per_chunk):
-                group_end = min(group_start + layers_per_chunk, total_layers) - 1
-                data_path = output_dir / f"model.onnx_data_{data_idx}"
-
-                group_tensors: list[TensorProto] = []
-                for layer_idx in range(group_start, group_end + 1):
-                    group_tensors.extend(classify_result.layers.get(layer_idx, []))
-
-                if not group_tensors:
-                    continue
-
-                shard_id = f"layers_{group_start}-{group_end}"
-                shard = _write_shard(
-                    group_tensors, shard_id, "layer", data_path,
-                    f"layers {group_start}-{group_end}",
-                    layer_range=(group_start, group_end),
+        for group_start, group_end in layer_ranges:
+            group_tensors = _layer_group(classify_result, group_start, group_end)
+            if not group_tensors:
+                print(f"  警告: layers {group_start}-{group_end} 没有非空 tensor，跳过该分片", file=sys.stderr)
+                continue
+
+            plans.append(ShardPlan(
+                layer_shard_id(group_start, group_end, split_base=False), "layer",
+                data_filename(data_idx),
+                f"layers {group_start}-{group_end}", group_tensors,
+                layer_range=(group_start, group_end),
+            ))
+            data_idx += 1
+
+    if max_file_bytes is not None:
+        for plan in plans:
+            size = estimate_output_bytes([plan], align)
+            if size > max_file_bytes:
+                raise ValidationError(
+                    f"{plan.filename} ({plan.label}) 最多 {size} 字节 (含对齐补零)，"
+                    f"超过单文件上限 {max_file_bytes} 字节"
                 )
-                if shard:
-                    shards.append(shard)
-                data_idx += 1
+
+    if shuffle_seed is not None:
+        for plan in plans:
+            shuffle_tensors(plan, classify_result, shuffle_seed)
+
+    return plans
+
+
+def plan_excluded_base(
+    classify_result: ClassifyResult, base_include: list[str], extension: str | None = None,
+) -> ShardPlan | None:
+    """--base-include 未列出的 base 分组，合并写入 model.onnx_data_base_excluded。
+
+    该文件同样是 model.onnx 引用的 external data，但不是分片：不列入 manifest，
+    按 manifest 下载的客户端不会获取它，需要由使用方自行提供。
+    没有带数据的 tensor 时返回 None。
+    """
+    groups = [group for group in BASE_GROUPS if group not in base_include]
+    tensors = _with_data([
+        tensor for group in groups for tensor in base_group_tensors(classify_result, group)
+    ])
+    if not tensors:
+        return None
+    filename = f"{EXCLUDED_BASE_FILENAME}.{extension}" if extension else EXCLUDED_BASE_FILENAME
+    return ShardPlan("base_excluded", "embed", filename, f"base ({' + '.join(groups)})", tensors)
+
+
+def plan_excluded_layers(
+    classify_result: ClassifyResult, only_layers: list[int], extension: str | None = None,
+) -> ShardPlan | None:
+    """--only-layers 未选中的层，合并写入 model.onnx_data_layers_excluded。
+
+    与 plan_excluded_base 相同，该文件不列入 manifest；未选中的层不再内联在
+    model.onnx 中，大模型的 model.onnx 也不会超出 protobuf 的 2 GB 上限。
+    全部层都被选中 (或未选中的层没有数据) 时返回 None。
+    """
+    selected = set(only_layers)
+    layers = [layer for layer in sorted(classify_result.layers) if layer not in selected]
+    tensors = _with_data([
+        tensor for layer in layers for tensor in classify_result.layers[layer]
+    ])
+    if not tensors:
+        return None
+    filename = f"{EXCLUDED_LAYERS_FILENAME}.{extension}" if extension else EXCLUDED_LAYERS_FILENAME
+    return ShardPlan("layers_excluded", "layer", filename, f"未选中的 {len(layers)} 层", tensors)
+
+
+def shuffle_tensors(plan: ShardPlan, classify_result: ClassifyResult, seed: int) -> None:
+    """按种子确定性地打乱分片内的 tensor 顺序 (原地修改 plan.tensors)。
+
+    每个分片用 `<seed>:<分片 id>` 单独播种，顺序只取决于种子和该分片自身，
+    与其他分片无关。layer 分片只在每层内部打乱、层与层仍按层号排列，
+    保证 layer_digests 的逐层区间连续。读取方按 model.onnx 中各 tensor 的
+    offset 定位数据，不依赖顺序，因此无需还原。
+    """
+    rng = random.Random(f"{seed}:{plan.shard_id}")
+    if plan.layer_range is None:
+        rng.shuffle(plan.tensors)
+        return
+    names = {t.name for t in plan.tensors}
+    tensors: list[TensorProto] = []
+    for layer_idx in range(plan.layer_range[0], plan.layer_range[1] + 1):
+        group = [t for t in classify_result.layers.get(layer_idx, []) if t.name in names]
+        rng.shuffle(group)
+        tensors.extend(group)
+    plan.tensors = tensors
+
+
+def check_no_clobber(plans: list[ShardPlan], output_dir: Path) -> None:
+    """确认本次将写入的文件 (plans 中各文件及 model.onnx) 都不存在，否则报错。"""
+    targets = [output_dir / plan.filename for plan in plans] + [output_dir / "model.onnx"]
+    existing = [p.name for p in targets if p.exists()]
+    if existing:
+        raise OutputError(
+            f"--no-clobber: 输出目录已存在 {len(existing)} 个目标文件 "
+            f"({', '.join(existing)})，使用 --force 覆盖或换一个输出目录"
+        )
+
+
+def write_shards(
+    model: onnx.ModelProto,
+    plans: list[ShardPlan],
+    output_dir: Path,
+    overwrite: bool = True,
+    cancel: threading.Event | None = None,
+    align: int = 1,
+    fsync: bool = False,
+    excluded: list[ShardPlan] | None = None,
+) -> list[Shard]:
+    """将模型写为精简 ONNX + 编号 external data 文件。
+
+    未出现在 plans 和 excluded 中的 tensor 保留为 model.onnx 内联数据。
+
+    Args:
+        model: 加载的 ONNX 模型
+        plans: plan_shards 生成的分片规划
+        output_dir: 输出目录
+        overwrite: 为 False 时若目标文件已存在则在写入任何文件前报错
+        cancel: 作为库嵌入时的取消信号，每个分片开始前检查；
+            置位后抛出 Cancelled，已写完的分片保持完整，model.onnx 不会写出
+        align: 分片内每个 tensor 偏移量的对齐字节数，1 表示紧密排列
+        fsync: 每个分片 (及其重命名) 和 model.onnx 写完后 fsync，
+            崩溃后不会留下 manifest 已引用但未落盘的分片
+        excluded: plan_excluded_base / plan_excluded_layers 的结果，与分片同样写出，
+            但不出现在返回值中
+
+    Returns:
+        Shard 列表（用于生成 manifest.json）
+    """
+    excluded = excluded or []
+    all_plans = plans + excluded
+    output_dir.mkdir(parents=True, exist_ok=True)
+    if not overwrite:
+        check_no_clobber(all_plans, output_dir)
+    check_disk_space(all_plans, output_dir, align)
+
+    shards: list[Shard] = []
+    for plan in plans:
+        if cancel is not None and cancel.is_set():
+            raise Cancelled(f"已取消，完成 {len(shards)}/{len(plans)} 个分片")
+        shards.append(_write_shard(plan, output_dir, align, fsync))
+    for plan in excluded:
+        _write_shard(plan, output_dir, align, fsync)
+        print("    (不列入 manifest)")
 
     # --- 保存精简 model.onnx ---
-    model_path = output_dir / "model.onnx"
-    onnx.save(model, str(model_path))
+    model_path = _save_model(model, output_dir, fsync)
     model_size = model_path.stat().st_size
     print(f"  model.onnx: graph only ({model_size / 1024 / 1024:.1f} MB)")
 
     return shards
+
+
+def embed_manifest(
+    model: onnx.ModelProto, manifest: ModelManifest, output_dir: Path, fsync: bool = False,
+) -> None:
+    """将 manifest JSON 写入 model.onnx 的 metadata_props 并重新保存。
+
+    model.onnx 本身不是分片、不在 manifest 中记录 hash，嵌入完整 manifest
+    不存在自引用问题。客户端只拿到 model.onnx 也能发现全部分片。
+    fsync 与 write_shards 相同：重新保存的 model.onnx 及其目录也要落盘。
+    """
+    for i, entry in enumerate(model.metadata_props):
+        if entry.key == MANIFEST_METADATA_KEY:
+            del model.metadata_props[i]
+            break
+    entry = model.metadata_props.add()
+    entry.key = MANIFEST_METADATA_KEY
+    entry.value = json.dumps(manifest.to_dict(), ensure_ascii=False)
+
+    model_path = _save_model(model, output_dir, fsync)
+    print(f"已将 manifest 嵌入 {model_path} (metadata key: {MANIFEST_METADATA_KEY})")
@@ -0,0 +1,64 @@
+"""测试共用的 fixture：基于 selftest.build_tiny_model() 的微型模型及其分片输出。"""
+
+import sys
+from pathlib import Path
+
+import onnx
+import pytest
+
+import main as sharder_main
+from src.cli import parse_args
+from src.parser import classify_initializers
+from src.selftest import build_tiny_model
+from src.writer import ModelManifest, plan_shards, write_shards
+
+
+@pytest.fixture
+def tiny_model():
+    return build_tiny_model()
+
+
+@pytest.fixture
+def shard_tiny():
+    """把一个新的微型模型分片写到 output_dir，返回 (manifest, classify_result)。
+
+    plan_kwargs 原样传给 plan_shards (默认每层一个分片)，write_kwargs 传给 write_shards。
+    """
+
+    def run(output_dir: Path, write_kwargs: dict | None = None, **plan_kwargs):
+        model = build_tiny_model()
+        result = classify_initializers(list(model.graph.initializer), model.graph)
+        plan_kwargs.setdefault("layers_per_chunk", 1)
+        plans = plan_shards(result, **plan_kwargs)
+        shards = write_shards(model, plans, output_dir, **(write_kwargs or {}))
+        manifest = ModelManifest(
+            model_id="pb-ai/test",
+            variant="base",
+            framework="onnxruntime-web",
+            dtype="fp32",
+            total_layers=result.max_layer + 1,
+            shards=shards,
+        )
+        manifest.write(output_dir)
+        return manifest, result
+
+    return run
+
+
+@pytest.fixture
+def tiny_model_path(tmp_path):
+    path = tmp_path / "input" / "model.onnx"
+    path.parent.mkdir()
+    onnx.save(build_tiny_model(), str(path))
+    return path
+
+
+@pytest.fixture
+def run_cli(monkeypatch):
+    """以给定命令行参数调用 main.main()，与 `python main.py ...` 等价 (不经过退出码转换)。"""
+
+    def run(*argv: str):
+        monkeypatch.setattr(sys, "argv", ["main.py", *map(str, argv)])
+        sharder_main.main(parse_args())
+
+    return run
@@ -0,0 +1,43 @@
+"""--align: 分片内 tensor 偏移对齐，补零计入 Shard.bytes。"""
+
+import onnx
+
+from src.parser import classify_initializers
+from src.writer import padding_bytes, plan_shards, write_shards
+
+
+def _write(tiny_model, output_dir, align):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    plans = plan_shards(result, layers_per_chunk=1)
+    return plans, write_shards(tiny_model, plans, output_dir, align=align)
+
+
+def test_no_padding_without_alignment(tmp_path, tiny_model):
+    plans, shards = _write(tiny_model, tmp_path, align=1)
+    assert padding_bytes(plans, shards) == 0
+
+
+def test_padding_matches_known_layout(tmp_path, tiny_model):
+    # embed 分片: embed_tokens 8x4 float32 = 128 字节，norm 16 字节；
+    # 对齐到 48 时 norm 从 144 开始，补零 16 字节。其余分片各只有一个 tensor，不补零。
+    plans, shards = _write(tiny_model, tmp_path, align=48)
+    assert padding_bytes(plans, shards) == 16
+    assert shards[0].bytes == 128 + 16 + 16
+    assert (tmp_path / shards[0].filename).read_bytes()[128:144] == b"\0" * 16
+
+
+def test_offsets_are_aligned(tmp_path, tiny_model):
+    _write(tiny_model, tmp_path, align=48)
+    model = onnx.load(str(tmp_path / "model.onnx"), load_external_data=False)
+    for tensor in model.graph.initializer:
+        offset = {e.key: e.value for e in tensor.external_data}["offset"]
+        assert int(offset) % 48 == 0
+
+
+def test_summary_reports_padding_only_when_aligned(tmp_path, tiny_model_path, run_cli, capsys):
+    run_cli("--input", tiny_model_path, "--output", tmp_path / "plain", "--model-id", "t/m")
+    assert "对齐补零" not in capsys.readouterr().out
+
+    run_cli("--input", tiny_model_path, "--output", tmp_path / "aligned", "--model-id", "t/m", "--align", "48")
+    total = 128 + 16 + 16 + 2 * 64 + 128
+    assert f"  对齐补零: 16 字节 ({16 / total * 100:.2f}%，--align 48)" in capsys.readouterr().out
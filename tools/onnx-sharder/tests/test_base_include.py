@@ -0,0 +1,50 @@
+"""--base-include: 只把部分 base 分组写入分片，其余写到 manifest 之外的文件。"""
+
+import json
+
+import onnx
+
+from src.writer.regenerate import _tensor_location
+
+
+def _locations(output):
+    model = onnx.load(str(output / "model.onnx"), load_external_data=False)
+    return {t.name: _tensor_location(t) for t in model.graph.initializer}
+
+
+def test_excluded_groups_leave_the_manifest_and_model_onnx(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+            "--base-include", "lm_head,norm")
+
+    manifest = json.loads((output / "manifest.json").read_text())
+    assert manifest["included_base"] == ["norm", "lm_head"]
+    assert "partial" not in manifest
+    filenames = [s["filename"] for s in manifest["shards"]]
+    assert "model.onnx_data_base_excluded" not in filenames
+    assert "model.onnx_data_embed" in filenames and "model.onnx_data_lm_head" in filenames
+
+    # embed_tokens 不再内联在 model.onnx 中，而是引用独立的 external data 文件
+    locations = _locations(output)
+    assert locations["model.embed_tokens.weight"] == "model.onnx_data_base_excluded"
+    assert locations["model.norm.weight"] == "model.onnx_data_embed"
+    assert (output / "model.onnx_data_base_excluded").is_file()
+
+
+def test_manifest_only_skips_the_excluded_file(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+            "--base-include", "lm_head,norm")
+    original = json.loads((output / "manifest.json").read_text())
+
+    run_cli("--output", output, "--model-id", "t/m", "--manifest-only", "--base-include", "lm_head,norm")
+    regenerated = json.loads((output / "manifest.json").read_text())
+    assert regenerated["shards"] == original["shards"]
+    assert "partial" not in regenerated
+
+
+def test_all_groups_included_writes_no_extra_file(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+            "--base-include", "embed_tokens,norm,lm_head")
+    assert not (output / "model.onnx_data_base_excluded").exists()
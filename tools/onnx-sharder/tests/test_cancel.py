@@ -0,0 +1,37 @@
+"""write_shards(cancel=...): 嵌入调用方可在分片之间取消，已写完的分片保持完整。"""
+
+import threading
+
+import pytest
+
+from src.errors import Cancelled
+from src.parser import classify_initializers
+from src.writer import plan_shards, shard_writer, write_shards
+
+
+def test_cancel_after_first_shard_stops_early(tmp_path, tiny_model, monkeypatch):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    plans = plan_shards(result, layers_per_chunk=1)
+    cancel = threading.Event()
+    real_write_shard = shard_writer._write_shard
+
+    def write_then_cancel(*args, **kwargs):
+        shard = real_write_shard(*args, **kwargs)
+        cancel.set()
+        return shard
+
+    monkeypatch.setattr(shard_writer, "_write_shard", write_then_cancel)
+    with pytest.raises(Cancelled, match=f"完成 1/{len(plans)}"):
+        write_shards(tiny_model, plans, tmp_path, cancel=cancel)
+
+    # 只剩第一个完整的分片：没有临时文件，也没有 model.onnx
+    assert [p.name for p in tmp_path.iterdir()] == [plans[0].filename]
+    assert (tmp_path / plans[0].filename).stat().st_size == shard_writer.planned_file_bytes(plans[0].tensors)
+
+
+def test_unset_event_writes_everything(tmp_path, tiny_model):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    plans = plan_shards(result, layers_per_chunk=1)
+    shards = write_shards(tiny_model, plans, tmp_path, cancel=threading.Event())
+    assert len(shards) == len(plans)
+    assert (tmp_path / "model.onnx").is_file()
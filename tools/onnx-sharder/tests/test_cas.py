@@ -0,0 +1,69 @@
+"""--cas-output: 按内容 hash 另存分片。"""
+
+import shutil
+
+from src.parser import classify_initializers
+from src.selftest import build_tiny_model
+from src.writer import plan_shards, store_content_addressed, write_shards
+from src.writer.manifest import blake3_file
+
+
+def _shard_model(model, output_dir):
+    result = classify_initializers(list(model.graph.initializer), model.graph)
+    return write_shards(model, plan_shards(result, layers_per_chunk=1), output_dir)
+
+
+def _other_model_sharing_layer_0():
+    """与 build_tiny_model() 只有第 0 层权重相同的另一个模型。"""
+    model = build_tiny_model()
+    for tensor in model.graph.initializer:
+        if tensor.name != "onnx::MatMul_100":
+            data = bytearray(tensor.raw_data)
+            data[0] ^= 0xFF
+            tensor.raw_data = bytes(data)
+    return model
+
+
+def test_copies_are_independent_of_output(tmp_path, shard_tiny):
+    output, cas = tmp_path / "out", tmp_path / "cas"
+    manifest, _ = shard_tiny(output)
+
+    assert store_content_addressed(manifest.shards, output, cas) == len(manifest.shards)
+    # 就地改写输出分片不影响存储对象
+    shard = manifest.shards[0]
+    with open(output / shard.filename, "r+b") as f:
+        f.write(b"\xff")
+    assert blake3_file(cas / shard.hash) == shard.hash
+
+
+def test_existing_objects_are_skipped(tmp_path, shard_tiny):
+    output, cas = tmp_path / "out", tmp_path / "cas"
+    manifest, _ = shard_tiny(output)
+    store_content_addressed(manifest.shards, output, cas)
+    assert store_content_addressed(manifest.shards, output, cas) == 0
+
+
+def test_link_shares_the_output_file(tmp_path, shard_tiny):
+    output, cas = tmp_path / "out", tmp_path / "cas"
+    manifest, _ = shard_tiny(output)
+    store_content_addressed(manifest.shards, output, cas, link=True)
+    shard = manifest.shards[0]
+    assert (cas / shard.hash).stat().st_ino == (output / shard.filename).stat().st_ino
+
+
+def test_layer_shared_by_two_models_is_stored_once(tmp_path, monkeypatch):
+    cas = tmp_path / "cas"
+    first = _shard_model(build_tiny_model(), tmp_path / "a")
+    second = _shard_model(_other_model_sharing_layer_0(), tmp_path / "b")
+    shared = {s.hash for s in first} & {s.hash for s in second}
+    assert shared == {next(s.hash for s in first if s.id == "layer_0")}
+
+    copied = []
+    real_copy = shutil.copyfile
+    monkeypatch.setattr(shutil, "copyfile", lambda src, dst: (copied.append(src), real_copy(src, dst)))
+    assert store_content_addressed(first, tmp_path / "a", cas) == len(first)
+    assert store_content_addressed(second, tmp_path / "b", cas) == len(second) - 1
+    # 共享的层只从第一个模型复制过一次，第二个模型的 layer_0 没有被读取
+    assert tmp_path / "b" / "model.onnx_data_0" not in copied
+    assert copied.count(tmp_path / "a" / "model.onnx_data_0") == 1
+    assert sorted(p.name for p in cas.iterdir()) == sorted({s.hash for s in first + second})
@@ -0,0 +1,28 @@
+"""--chunk-meta: 每个分片附加相同的自定义元数据。"""
+
+import json
+
+from src.writer import ModelManifest
+
+
+def test_chunk_meta_on_every_shard_and_round_trips(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+            "--chunk-meta", "training_step=1200", "--chunk-meta", "run=a=b")
+
+    data = json.loads((output / "manifest.json").read_text())
+    expected = {"run": "a=b", "training_step": "1200"}
+    assert len(data["shards"]) == 4
+    assert all(entry["metadata"] == expected for entry in data["shards"])
+
+    manifest = ModelManifest.from_dict(data)
+    assert all(shard.metadata == expected for shard in manifest.shards)
+    assert manifest.to_dict() == data
+
+
+def test_no_metadata_without_flag(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m")
+    data = json.loads((output / "manifest.json").read_text())
+    assert all("metadata" not in entry for entry in data["shards"])
+    assert all(shard.metadata is None for shard in ModelManifest.from_dict(data).shards)
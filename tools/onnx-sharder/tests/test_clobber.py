@@ -0,0 +1,36 @@
+"""--force / --no-clobber: 输出目录中已有目标文件时的处理策略。"""
+
+import pytest
+
+from src.errors import OutputError
+
+
+def _prepopulate(output_dir):
+    output_dir.mkdir()
+    (output_dir / "model.onnx_data_0").write_bytes(b"previous good split")
+
+
+def test_no_clobber_refuses_existing_files(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    _prepopulate(output)
+
+    with pytest.raises(OutputError, match="model.onnx_data_0"):
+        run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", "--no-clobber")
+    assert (output / "model.onnx_data_0").read_bytes() == b"previous good split"
+    assert sorted(p.name for p in output.iterdir()) == ["model.onnx_data_0"]
+
+
+@pytest.mark.parametrize("flags", [(), ("--force",)])
+def test_overwrite_is_default_and_force(tmp_path, tiny_model_path, run_cli, flags):
+    output = tmp_path / "out"
+    _prepopulate(output)
+
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", *flags)
+    assert (output / "model.onnx_data_0").read_bytes() != b"previous good split"
+    assert (output / "manifest.json").is_file()
+
+
+def test_no_clobber_allows_fresh_directory(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", "--no-clobber")
+    assert (output / "manifest.json").is_file()
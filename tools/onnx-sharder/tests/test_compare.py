@@ -0,0 +1,57 @@
+"""--compare: 按 manifest 比较两份输出，并校验两侧的分片文件。"""
+
+import shutil
+
+from src.verify import run_compare
+
+
+def _corrupt(path):
+    data = bytearray(path.read_bytes())
+    data[0] ^= 0xFF
+    path.write_bytes(bytes(data))
+
+
+def _marks(capsys):
+    return [line.split()[0] for line in capsys.readouterr().out.splitlines() if line.strip().startswith("[")]
+
+
+def test_identical_outputs_compare_equal(tmp_path, shard_tiny, capsys):
+    shard_tiny(tmp_path / "out")
+    shard_tiny(tmp_path / "ref")
+    assert run_compare(tmp_path / "out", tmp_path / "ref")
+    assert set(_marks(capsys)) == {"[SAME]"}
+
+
+def test_reference_may_be_a_manifest_file(tmp_path, shard_tiny):
+    shard_tiny(tmp_path / "out")
+    shard_tiny(tmp_path / "ref")
+    assert run_compare(tmp_path / "out", tmp_path / "ref" / "manifest.json")
+
+
+def test_different_layer_ranges_are_reported(tmp_path, shard_tiny, capsys):
+    shard_tiny(tmp_path / "out", layers_per_chunk=2)
+    shard_tiny(tmp_path / "ref")
+    assert not run_compare(tmp_path / "out", tmp_path / "ref")
+    marks = _marks(capsys)
+    assert "[MISSING]" in marks and "[EXTRA]" in marks
+
+
+def test_corrupt_file_on_either_side_is_a_difference(tmp_path, shard_tiny, capsys):
+    shard_tiny(tmp_path / "out")
+    shard_tiny(tmp_path / "ref")
+    _corrupt(tmp_path / "out" / "model.onnx_data_0")
+    assert not run_compare(tmp_path / "out", tmp_path / "ref")
+    assert _marks(capsys).count("[BAD]") == 1
+
+    shutil.copy(tmp_path / "ref" / "model.onnx_data_0", tmp_path / "out" / "model.onnx_data_0")
+    (tmp_path / "ref" / "model.onnx_data_lm_head").unlink()
+    assert not run_compare(tmp_path / "out", tmp_path / "ref")
+    assert _marks(capsys).count("[BAD]") == 1
+
+
+def test_output_manifest_path_is_honoured(tmp_path, shard_tiny):
+    shard_tiny(tmp_path / "out")
+    shard_tiny(tmp_path / "ref")
+    moved = tmp_path / "elsewhere.json"
+    (tmp_path / "out" / "manifest.json").rename(moved)
+    assert run_compare(tmp_path / "out", tmp_path / "ref", manifest_path=moved)
@@ -0,0 +1,43 @@
+"""写入前的磁盘空间预检 (check_disk_space)。"""
+
+import shutil
+from collections import namedtuple
+
+import pytest
+
+from src.errors import OutputError
+from src.parser import classify_initializers
+from src.writer import plan_shards, write_shards
+from src.writer.shard_writer import estimate_output_bytes
+
+_Usage = namedtuple("_Usage", "total used free")
+
+
+def _fake_free(monkeypatch, free: int) -> None:
+    monkeypatch.setattr(shutil, "disk_usage", lambda path: _Usage(free * 2, free, free))
+
+
+def test_preflight_fails_before_writing_when_space_is_short(tmp_path, monkeypatch, tiny_model):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    plans = plan_shards(result, layers_per_chunk=1)
+    _fake_free(monkeypatch, estimate_output_bytes(plans) - 1)
+
+    with pytest.raises(OutputError, match="磁盘空间不足"):
+        write_shards(tiny_model, plans, tmp_path)
+    assert list(tmp_path.iterdir()) == []
+
+
+def test_preflight_passes_with_exact_space(tmp_path, monkeypatch, tiny_model):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    plans = plan_shards(result, layers_per_chunk=1)
+    _fake_free(monkeypatch, estimate_output_bytes(plans))
+
+    shards = write_shards(tiny_model, plans, tmp_path)
+    assert len(shards) == len(plans)
+
+
+def test_estimate_includes_worst_case_alignment(tiny_model):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    plans = plan_shards(result, layers_per_chunk=1)
+    tensors = sum(len(plan.tensors) for plan in plans)
+    assert estimate_output_bytes(plans, align=64) == estimate_output_bytes(plans) + 63 * tensors
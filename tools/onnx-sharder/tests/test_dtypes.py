@@ -0,0 +1,110 @@
+"""dtype_bytes / tensor_nbytes / stored_data_bytes: 按实际存储格式计算与序列化。"""
+
+import struct
+
+import pytest
+from onnx import TensorProto
+
+from src.errors import ValidationError
+from src.parser import dtype_bytes, dtype_histogram, stored_data_bytes, tensor_nbytes
+from src.writer import ShardPlan, write_shards
+from src.selftest import build_tiny_model
+
+ELEMENT_BYTES = {
+    "FLOAT": 4, "UINT8": 1, "INT8": 1, "UINT16": 2, "INT16": 2, "INT32": 4, "INT64": 8,
+    "BOOL": 1, "FLOAT16": 2, "DOUBLE": 8, "UINT32": 4, "UINT64": 8, "COMPLEX64": 8,
+    "COMPLEX128": 16, "BFLOAT16": 2, "FLOAT8E4M3FN": 1, "FLOAT8E4M3FNUZ": 1,
+    "FLOAT8E5M2": 1, "FLOAT8E5M2FNUZ": 1,
+}
+PACKED_4BIT = [name for name in ("UINT4", "INT4", "FLOAT4E2M1") if hasattr(TensorProto, name)]
+
+
+@pytest.mark.parametrize("name", sorted(ELEMENT_BYTES))
+def test_dtype_bytes(name):
+    assert dtype_bytes(getattr(TensorProto, name)) == ELEMENT_BYTES[name]
+
+
+@pytest.mark.parametrize("name", PACKED_4BIT + ["STRING", "UNDEFINED"])
+def test_variable_or_packed_types_have_no_element_size(name):
+    assert dtype_bytes(getattr(TensorProto, name)) is None
+
+
+@pytest.mark.parametrize("name", sorted(ELEMENT_BYTES))
+def test_tensor_nbytes_from_dims(name):
+    tensor = TensorProto(name="w", dims=[3, 5], data_type=getattr(TensorProto, name))
+    assert tensor_nbytes(tensor) == 15 * ELEMENT_BYTES[name]
+
+
+@pytest.mark.parametrize("name", PACKED_4BIT)
+def test_tensor_nbytes_packs_two_4bit_elements_per_byte(name):
+    tensor = TensorProto(name="w", dims=[3, 5], data_type=getattr(TensorProto, name))
+    assert tensor_nbytes(tensor) == 8
+
+
+def test_tensor_nbytes_prefers_raw_data_and_external_length():
+    assert tensor_nbytes(TensorProto(name="w", dims=[4], data_type=TensorProto.BFLOAT16, raw_data=bytes(8))) == 8
+    external = TensorProto(name="w", dims=[4], data_type=TensorProto.BFLOAT16)
+    external.data_location = TensorProto.EXTERNAL
+    external.external_data.add(key="location", value="model.onnx_data_0")
+    external.external_data.add(key="length", value="8")
+    assert tensor_nbytes(external) == 8
+
+
+def test_zero_dim_tensor_has_no_bytes():
+    assert tensor_nbytes(TensorProto(name="w", dims=[0, 4], data_type=TensorProto.FLOAT)) == 0
+
+
+@pytest.mark.parametrize("name, field, values, expected", [
+    # BFLOAT16 / FLOAT16 / FP8 在 int32_data 中存位模式，写出时只取低位字节
+    ("BFLOAT16", "int32_data", [0x3F80, 0xC000], struct.pack("<2H", 0x3F80, 0xC000)),
+    ("FLOAT16", "int32_data", [0x3C00, 0x7BFF], struct.pack("<2H", 0x3C00, 0x7BFF)),
+    ("FLOAT8E4M3FN", "int32_data", [0x38, 0xB8], bytes([0x38, 0xB8])),
+    ("INT8", "int32_data", [-1, 5], struct.pack("<2b", -1, 5)),
+    ("INT32", "int32_data", [-2, 7], struct.pack("<2i", -2, 7)),
+    ("FLOAT", "float_data", [1.5, -2.0], struct.pack("<2f", 1.5, -2.0)),
+    ("DOUBLE", "double_data", [0.25, 3.0], struct.pack("<2d", 0.25, 3.0)),
+    ("INT64", "int64_data", [-3, 2**40], struct.pack("<2q", -3, 2**40)),
+    ("UINT32", "uint64_data", [1, 2**32 - 1], struct.pack("<2I", 1, 2**32 - 1)),
+])
+def test_stored_fields_are_written_at_their_element_size(name, field, values, expected):
+    tensor = TensorProto(name="w", dims=[2], data_type=getattr(TensorProto, name), **{field: values})
+    assert stored_data_bytes(tensor) == expected
+    assert len(expected) == tensor_nbytes(tensor)
+
+
+def test_string_tensor_cannot_be_written():
+    tensor = TensorProto(name="s", dims=[1], data_type=TensorProto.STRING, string_data=[b"x"])
+    with pytest.raises(ValidationError, match="STRING"):
+        stored_data_bytes(tensor)
+
+
+def test_field_length_must_match_dims():
+    tensor = TensorProto(name="w", dims=[3], data_type=TensorProto.BFLOAT16, int32_data=[1, 2])
+    with pytest.raises(ValidationError, match="应有的 6 字节"):
+        stored_data_bytes(tensor)
+
+
+def test_bf16_tensor_without_raw_data_is_written_at_two_bytes(tmp_path):
+    tensor = TensorProto(name="w", dims=[4], data_type=TensorProto.BFLOAT16, int32_data=[1, 2, 3, 4])
+    plan = ShardPlan("embed", "embed", "model.onnx_data_embed", "embed", [tensor])
+    [shard] = write_shards(build_tiny_model(), [plan], tmp_path)
+    assert shard.bytes == 8
+    assert (tmp_path / shard.filename).read_bytes() == struct.pack("<4H", 1, 2, 3, 4)
+
+
+def test_dtype_histogram_counts_and_bytes_sorted_by_bytes():
+    tensors = [
+        TensorProto(name="a", dims=[4], data_type=TensorProto.FLOAT),
+        TensorProto(name="b", dims=[100], data_type=TensorProto.INT8),
+        TensorProto(name="c", dims=[2, 3], data_type=TensorProto.FLOAT),
+        TensorProto(name="d", dims=[3], data_type=TensorProto.FLOAT16),
+        TensorProto(name="e", dims=[0, 8], data_type=TensorProto.FLOAT16),
+    ]
+    histogram = dtype_histogram(tensors)
+    assert histogram == {
+        "INT8": {"count": 1, "bytes": 100},
+        "FLOAT": {"count": 2, "bytes": 16 + 24},
+        "FLOAT16": {"count": 2, "bytes": 6},
+    }
+    assert list(histogram) == ["INT8", "FLOAT", "FLOAT16"]
+    assert dtype_histogram([]) == {}
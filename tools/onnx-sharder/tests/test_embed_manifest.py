@@ -0,0 +1,49 @@
+"""--embed-manifest: manifest 嵌入 model.onnx 的 metadata_props，可原样取回。"""
+
+import json
+
+import onnx
+
+from src.writer import ModelManifest, shard_writer
+from src.writer.shard_writer import MANIFEST_METADATA_KEY
+
+
+def _embedded(model_path):
+    model = onnx.load(str(model_path), load_external_data=False)
+    values = [entry.value for entry in model.metadata_props if entry.key == MANIFEST_METADATA_KEY]
+    assert len(values) == 1
+    return json.loads(values[0])
+
+
+def test_embedded_manifest_round_trips(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", "--embed-manifest")
+
+    embedded = _embedded(output / "model.onnx")
+    assert embedded == json.loads((output / "manifest.json").read_text())
+    manifest = ModelManifest.from_dict(embedded)
+    assert [s.filename for s in manifest.shards] == [
+        "model.onnx_data_embed", "model.onnx_data_0", "model.onnx_data_1", "model.onnx_data_lm_head",
+    ]
+    # 嵌入后的 model.onnx 仍引用原有分片，各分片 hash 不受影响
+    for shard in manifest.shards:
+        shard.verify_file(shard.resolve(output))
+
+
+def test_embedding_twice_keeps_one_entry(tmp_path, shard_tiny, tiny_model):
+    manifest, _ = shard_tiny(tmp_path)
+    shard_writer.embed_manifest(tiny_model, manifest, tmp_path)
+    shard_writer.embed_manifest(tiny_model, manifest, tmp_path)
+    assert _embedded(tmp_path / "model.onnx")["model_id"] == manifest.model_id
+
+
+def test_fsync_covers_the_resaved_model(tmp_path, tiny_model_path, run_cli, monkeypatch):
+    synced = []
+    real_fsync = shard_writer.fsync_path
+    monkeypatch.setattr(shard_writer, "fsync_path", lambda p: (synced.append(p), real_fsync(p)))
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+            "--embed-manifest", "--fsync")
+    # 最后一次写入 model.onnx 是嵌入 manifest 时的重新保存：临时文件 fsync 后重命名，再 fsync 目录
+    assert synced[-2].name.startswith(".model.onnx.") and synced[-2].parent == output
+    assert synced[-1] == output
@@ -0,0 +1,50 @@
+"""0 字节 tensor (某一维为 0) 保留内联；全部为空的分组不生成分片并给出警告。"""
+
+from onnx import TensorProto
+
+from src.parser import ClassifyResult
+from src.selftest import build_tiny_model
+from src.writer import plan_shards, write_shards
+
+
+def _tensor(name: str, *dims: int) -> TensorProto:
+    count = 1
+    for d in dims:
+        count *= d
+    return TensorProto(name=name, dims=list(dims), data_type=TensorProto.FLOAT, raw_data=bytes(4 * count))
+
+
+def _result() -> ClassifyResult:
+    result = ClassifyResult()
+    result.embed = [_tensor("model.embed_tokens.weight", 4, 2)]
+    result.layers[0] = [_tensor("layers.0.w", 4), _tensor("layers.0.empty", 0, 4)]
+    result.layers[1] = [_tensor("layers.1.empty", 4, 0), _tensor("layers.1.scalar_empty", 0)]
+    result.max_layer = 1
+    return result
+
+
+def test_zero_dim_tensor_stays_inline(tmp_path):
+    result = _result()
+    plans = plan_shards(result, layers_per_chunk=1)
+    layer0 = next(plan for plan in plans if plan.layer_range == (0, 0))
+    assert [t.name for t in layer0.tensors] == ["layers.0.w"]
+
+    shards = write_shards(build_tiny_model(), plans, tmp_path)
+    assert next(s for s in shards if s.id == "layer_0").bytes == 16
+    empty = result.layers[0][1]
+    assert empty.data_location != TensorProto.EXTERNAL
+    assert list(empty.dims) == [0, 4]
+
+
+def test_all_empty_layer_gets_no_shard(capsys):
+    plans = plan_shards(_result(), layers_per_chunk=1)
+    assert [plan.shard_id for plan in plans] == ["embed", "layer_0"]
+    assert "layer 1 没有非空 tensor，跳过该分片" in capsys.readouterr().err
+
+
+def test_all_empty_chunk_inside_a_larger_range_is_kept_with_its_neighbours():
+    plans = plan_shards(_result(), layers_per_chunk=2)
+    assert [(plan.shard_id, [t.name for t in plan.tensors]) for plan in plans] == [
+        ("embed", ["model.embed_tokens.weight"]),
+        ("layers_0-1", ["layers.0.w"]),
+    ]
@@ -0,0 +1,69 @@
+"""以子进程运行 main.py，每类失败对应固定的退出码 (见 src/errors.py)。"""
+
+import json
+import subprocess
+import sys
+from pathlib import Path
+
+import pytest
+
+TOOL_DIR = Path(__file__).resolve().parent.parent
+
+
+def _run(*argv) -> subprocess.CompletedProcess:
+    return subprocess.run(
+        [sys.executable, "main.py", *map(str, argv)],
+        cwd=TOOL_DIR, capture_output=True, text=True,
+    )
+
+
+@pytest.fixture
+def sharded(tmp_path, shard_tiny):
+    shard_tiny(tmp_path / "out")
+    return tmp_path / "out"
+
+
+def test_success_is_zero(sharded):
+    assert _run("--verify", "--output", sharded).returncode == 0
+
+
+def test_usage_error_is_2(tmp_path):
+    assert _run("--output", tmp_path).returncode == 2
+
+
+def test_missing_input_is_3(tmp_path):
+    result = _run("--input", tmp_path / "missing.onnx", "--output", tmp_path / "out", "--model-id", "t/m")
+    assert result.returncode == 3
+    assert "输入模型不存在" in result.stderr
+
+
+def test_unreadable_tokenizer_is_3_not_5(tmp_path, tiny_model_path):
+    # tokenizer.json 是目录：读取失败属于输入错误，而不是输出 I/O 错误
+    tokenizer_dir = tmp_path / "tokenizer"
+    (tokenizer_dir / "tokenizer.json").mkdir(parents=True)
+    result = _run("--input", tiny_model_path, "--output", tmp_path / "out", "--model-id", "t/m",
+                  "--copy-tokenizer", tokenizer_dir, "--json-errors")
+    assert result.returncode == 3
+    assert json.loads(result.stderr.strip().splitlines()[-1])["error"] == "input"
+
+
+def test_invalid_manifest_is_4(sharded):
+    manifest = sharded / "manifest.json"
+    data = json.loads(manifest.read_text())
+    data["shards"][1]["layer_range"] = [1, 0]
+    manifest.write_text(json.dumps(data))
+    assert _run("--validate-only", manifest).returncode == 4
+
+
+def test_output_io_error_is_5(tmp_path, tiny_model_path):
+    blocker = tmp_path / "out"
+    blocker.write_text("not a directory")
+    assert _run("--input", tiny_model_path, "--output", blocker, "--model-id", "t/m").returncode == 5
+
+
+def test_corrupt_shard_is_6(sharded):
+    shard = sharded / "model.onnx_data_0"
+    data = bytearray(shard.read_bytes())
+    data[0] ^= 0xFF
+    shard.write_bytes(bytes(data))
+    assert _run("--verify", "--output", sharded).returncode == 6
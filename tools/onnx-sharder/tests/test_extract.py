@@ -0,0 +1,48 @@
+"""--extract: 只把指定的 initializer 写出，数据与源模型逐字节一致。"""
+
+import onnx
+import pytest
+
+from src.errors import ValidationError
+from src.parser import stored_data_bytes
+from src.writer import extract_tensors
+from src.writer.extract import EXTRACT_DATA, EXTRACT_MODEL
+
+SUBSET = ["onnx::MatMul_101", "model.norm.weight"]
+
+
+def _source_bytes(model, name):
+    tensor = next(t for t in model.graph.initializer if t.name == name)
+    return tensor.raw_data or stored_data_bytes(tensor)
+
+
+def test_extracted_subset_matches_source_bytes(tmp_path, tiny_model):
+    source = {name: _source_bytes(tiny_model, name) for name in SUBSET}
+    extract_tensors(tiny_model, SUBSET, tmp_path)
+
+    model = onnx.load(str(tmp_path / EXTRACT_MODEL), load_external_data=False)
+    assert [t.name for t in model.graph.initializer] == SUBSET
+    data = (tmp_path / EXTRACT_DATA).read_bytes()
+    for tensor in model.graph.initializer:
+        ref = {entry.key: entry.value for entry in tensor.external_data}
+        assert ref["location"] == EXTRACT_DATA
+        offset, length = int(ref["offset"]), int(ref["length"])
+        assert data[offset:offset + length] == source[tensor.name]
+    assert len(data) == sum(map(len, source.values()))
+    # 源模型不受影响
+    assert all(t.data_location != onnx.TensorProto.EXTERNAL for t in tiny_model.graph.initializer)
+
+
+def test_extracted_model_loads_back(tmp_path, tiny_model):
+    extract_tensors(tiny_model, SUBSET, tmp_path)
+    model = onnx.load(str(tmp_path / EXTRACT_MODEL))
+    assert {t.name: t.raw_data for t in model.graph.initializer} == {
+        name: _source_bytes(tiny_model, name) for name in SUBSET
+    }
+
+
+def test_unknown_name_writes_nothing(tmp_path, tiny_model):
+    output = tmp_path / "out"
+    with pytest.raises(ValidationError, match="onnx::MatMul_404"):
+        extract_tensors(tiny_model, ["model.norm.weight", "onnx::MatMul_404"], output)
+    assert not output.exists()
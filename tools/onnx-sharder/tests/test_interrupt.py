@@ -0,0 +1,52 @@
+"""Ctrl-C 中断写入时删除未完成的文件，不留下临时文件、残缺分片或被截断的 model.onnx。"""
+
+import pytest
+
+from src.parser import classify_initializers
+from src.writer import plan_shards, shard_writer, write_shards
+
+
+def _plans(model):
+    result = classify_initializers(list(model.graph.initializer), model.graph)
+    return plan_shards(result, layers_per_chunk=1)
+
+
+def test_interrupt_mid_shard_leaves_no_partial_file(tmp_path, tiny_model, monkeypatch):
+    plans = _plans(tiny_model)
+    calls = []
+    real_bytes = shard_writer._tensor_raw_bytes
+
+    def interrupt_on_second_tensor(tensor):
+        calls.append(tensor.name)
+        if len(calls) == 2:
+            raise KeyboardInterrupt
+        return real_bytes(tensor)
+
+    monkeypatch.setattr(shard_writer, "_tensor_raw_bytes", interrupt_on_second_tensor)
+    # embed 分片有 embed_tokens 和 norm 两个 tensor，第一个写完后中断
+    assert len(plans[0].tensors) == 2
+    with pytest.raises(KeyboardInterrupt):
+        write_shards(tiny_model, plans, tmp_path)
+    assert list(tmp_path.iterdir()) == []
+
+
+def test_interrupt_while_saving_model_leaves_no_truncated_model(tmp_path, tiny_model, monkeypatch):
+    plans = _plans(tiny_model)
+
+    def truncated_save(model, path):
+        with open(path, "wb") as f:
+            f.write(b"\x08")
+        raise KeyboardInterrupt
+
+    monkeypatch.setattr(shard_writer.onnx, "save", truncated_save)
+    with pytest.raises(KeyboardInterrupt):
+        write_shards(tiny_model, plans, tmp_path)
+    # 分片都已完整写出；model.onnx 及其临时文件都不存在
+    assert sorted(p.name for p in tmp_path.iterdir()) == sorted(plan.filename for plan in plans)
+
+
+def test_model_is_replaced_atomically(tmp_path, tiny_model):
+    write_shards(tiny_model, _plans(tiny_model), tmp_path)
+    names = sorted(p.name for p in tmp_path.iterdir())
+    assert "model.onnx" in names
+    assert not [name for name in names if name.endswith(".tmp")]
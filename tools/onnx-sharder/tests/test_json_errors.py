@@ -0,0 +1,51 @@
+"""--json-errors: 失败信息输出为 stderr 上的单个 JSON 对象。"""
+
+import json
+import sys
+
+import pytest
+
+from main import report_error
+from src.cli import parse_args
+from src.errors import InputError
+
+
+def _parse(monkeypatch, *argv):
+    monkeypatch.setattr(sys, "argv", ["main.py", *argv])
+    return parse_args()
+
+
+def test_usage_error_is_json(monkeypatch, capsys):
+    with pytest.raises(SystemExit) as exc:
+        _parse(monkeypatch, "--output", "out", "--model-id", "t/m", "--json-errors")
+    assert exc.value.code == 2
+    error = json.loads(capsys.readouterr().err)
+    assert error["error"] == "usage"
+    assert "--input" in error["message"]
+    assert error["context"] == []
+
+
+def test_bad_argument_value_is_json(monkeypatch, capsys):
+    with pytest.raises(SystemExit) as exc:
+        _parse(monkeypatch, "--json-errors", "--probe", "--input", "m.onnx", "--jobs", "0")
+    assert exc.value.code == 2
+    assert json.loads(capsys.readouterr().err)["error"] == "usage"
+
+
+def test_usage_error_without_flag_is_text(monkeypatch, capsys):
+    with pytest.raises(SystemExit) as exc:
+        _parse(monkeypatch, "--output", "out", "--model-id", "t/m")
+    assert exc.value.code == 2
+    assert "usage:" in capsys.readouterr().err
+
+
+def test_report_error_includes_cause_chain(capsys):
+    try:
+        try:
+            raise FileNotFoundError("model.onnx")
+        except FileNotFoundError as e:
+            raise InputError("无法加载模型") from e
+    except InputError as e:
+        report_error(e, e.category, str(e), json_errors=True)
+    error = json.loads(capsys.readouterr().err)
+    assert error == {"error": "input", "message": "无法加载模型", "context": ["model.onnx"]}
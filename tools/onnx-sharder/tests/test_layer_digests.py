@@ -0,0 +1,50 @@
+"""多层分片的逐层 hash (layer_digests)。"""
+
+import copy
+
+import pytest
+
+from src.errors import DigestMismatch
+from src.writer import assign_layer_digests
+
+
+def _shard_two_layers(shard_tiny, output_dir):
+    manifest, result = shard_tiny(output_dir, layers_per_chunk=2)
+    assign_layer_digests(result, manifest.shards, output_dir)
+    (shard,) = [s for s in manifest.shards if s.kind == "layer"]
+    return shard, result
+
+
+def test_digests_present_only_for_multi_layer_shards(tmp_path, shard_tiny):
+    manifest, result = shard_tiny(tmp_path, layers_per_chunk=1)
+    assign_layer_digests(result, manifest.shards, tmp_path)
+    assert all(s.layer_digests is None for s in manifest.shards)
+
+    shard, _ = _shard_two_layers(shard_tiny, tmp_path / "multi")
+    assert shard.id == "layers_0-1"
+    assert sorted(shard.layer_digests) == [0, 1]
+    # 两层区间相邻且覆盖整个分片
+    first, second = shard.layer_digests[0], shard.layer_digests[1]
+    assert first.offset == 0 and first.offset + first.length == second.offset
+    assert second.offset + second.length == shard.bytes
+
+
+def test_digests_are_stable_across_runs(tmp_path, shard_tiny):
+    a, _ = _shard_two_layers(shard_tiny, tmp_path / "a")
+    b, _ = _shard_two_layers(shard_tiny, tmp_path / "b")
+    assert a.layer_digests == b.layer_digests
+
+
+def test_altering_one_layer_changes_only_its_digest(tmp_path, shard_tiny):
+    shard, result = _shard_two_layers(shard_tiny, tmp_path)
+    before = copy.deepcopy(shard.layer_digests)
+    path = tmp_path / shard.filename
+    data = bytearray(path.read_bytes())
+    data[before[1].offset] ^= 0xFF
+    path.write_bytes(bytes(data))
+
+    with pytest.raises(DigestMismatch, match="损坏的层: 1\\)"):
+        shard.verify_file(path)
+    assign_layer_digests(result, [shard], tmp_path)
+    assert shard.layer_digests[0] == before[0]
+    assert shard.layer_digests[1].hash != before[1].hash
@@ -0,0 +1,50 @@
+"""--max-layer-index: 异常大的层号视为解析错误，不去规划海量分片。"""
+
+import onnx
+import pytest
+from onnx import TensorProto
+
+from src.errors import ValidationError
+from src.selftest import build_tiny_model
+
+HUGE = "model.layers.4294967295.weight"
+
+
+def _huge_tensor() -> TensorProto:
+    return TensorProto(name=HUGE, dims=[4], data_type=TensorProto.FLOAT, raw_data=bytes(16))
+
+
+def test_huge_layer_index_is_rejected(tmp_path, run_cli):
+    model = build_tiny_model()
+    model.graph.initializer.append(_huge_tensor())
+    path = tmp_path / "model.onnx"
+    onnx.save(model, str(path))
+
+    output = tmp_path / "out"
+    with pytest.raises(ValidationError, match=r"layer 4294967295 \(model\.layers\.4294967295\.weight\)"):
+        run_cli("--input", path, "--output", output, "--model-id", "t/m")
+    assert not output.exists()
+
+
+def test_cap_can_be_raised(tmp_path, run_cli):
+    model = build_tiny_model()
+    model.graph.initializer.append(
+        TensorProto(name="model.layers.1500.weight", dims=[4], data_type=TensorProto.FLOAT, raw_data=bytes(16))
+    )
+    path = tmp_path / "model.onnx"
+    onnx.save(model, str(path))
+    with pytest.raises(ValidationError, match="--max-layer-index 1000"):
+        run_cli("--input", path, "--output", tmp_path / "a", "--model-id", "t/m", "--only-layers", "0")
+    run_cli("--input", path, "--output", tmp_path / "b", "--model-id", "t/m",
+            "--only-layers", "0", "--max-layer-index", "2000")
+
+
+def test_manifest_only_applies_the_cap(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m")
+    model = onnx.load(str(output / "model.onnx"), load_external_data=False)
+    model.graph.initializer.append(_huge_tensor())
+    onnx.save(model, str(output / "model.onnx"))
+
+    with pytest.raises(ValidationError, match="层索引异常大"):
+        run_cli("--output", output, "--model-id", "t/m", "--manifest-only")
@@ -0,0 +1,24 @@
+"""--limit-chunks: 只写出前 N 个分片，manifest 标记为 partial。"""
+
+import json
+
+
+def test_limit_chunks_writes_only_first_n(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", "--limit-chunks", 2)
+
+    manifest = json.loads((output / "manifest.json").read_text())
+    assert [s["id"] for s in manifest["shards"]] == ["embed", "layer_0"]
+    assert manifest["partial"] is True
+    assert manifest["included_layers"] == [0]
+    data_files = sorted(p.name for p in output.glob("model.onnx_data*"))
+    assert data_files == ["model.onnx_data_0", "model.onnx_data_embed"]
+
+
+def test_full_run_is_not_partial(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m")
+
+    manifest = json.loads((output / "manifest.json").read_text())
+    assert len(manifest["shards"]) == 4
+    assert "partial" not in manifest
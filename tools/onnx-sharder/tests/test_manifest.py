@@ -0,0 +1,181 @@
+"""ModelManifest 的派生字段与便捷方法。"""
+
+import json
+
+import pytest
+
+from src.errors import ValidationError
+from src.writer import ModelManifest, Shard
+from src.writer.manifest import shard_dependencies
+
+
+def _hex(c: str) -> str:
+    return c * 64
+
+
+def make_manifest() -> ModelManifest:
+    return ModelManifest(
+        model_id="pb-ai/test",
+        variant="base",
+        framework="onnxruntime-web",
+        dtype="int8",
+        total_layers=4,
+        shards=[
+            Shard("embed", "embed", "model.onnx_data_embed", 100, _hex("a")),
+            Shard("layers_0-1", "layer", "model.onnx_data_0", 40, _hex("b"), layer_range=(0, 1)),
+            Shard("layers_2-3", "layer", "model.onnx_data_2", 30, _hex("c"), layer_range=(2, 3)),
+            Shard("lm_head", "lm_head", "model.onnx_data_lm_head", 80, _hex("d")),
+        ],
+    )
+
+
+def test_manifest_digest_is_stable_and_order_independent():
+    manifest = make_manifest()
+    reordered = make_manifest()
+    reordered.shards.reverse()
+    assert manifest.manifest_digest() == make_manifest().manifest_digest()
+    assert manifest.manifest_digest() == reordered.manifest_digest()
+    assert manifest.to_dict()["manifest_digest"] == f"blake3:{manifest.manifest_digest()}"
+
+
+@pytest.mark.parametrize("index", range(4))
+def test_changing_any_shard_hash_changes_manifest_digest(index):
+    original = make_manifest().manifest_digest()
+    manifest = make_manifest()
+    manifest.shards[index].hash = _hex("e")
+    assert manifest.manifest_digest() != original
+
+
+def test_total_bytes_sums_all_shards():
+    assert make_manifest().total_bytes() == 100 + 40 + 30 + 80
+
+
+def test_layer_count_uses_layer_shards_only():
+    assert make_manifest().layer_count() == 4
+    base_only = make_manifest()
+    base_only.shards = [s for s in base_only.shards if s.kind != "layer"]
+    assert base_only.layer_count() == 0
+
+
+@pytest.mark.parametrize("layer, expected", [(0, "layers_0-1"), (1, "layers_0-1"), (3, "layers_2-3")])
+def test_shard_for_layer(layer, expected):
+    assert make_manifest().shard_for_layer(layer).id == expected
+
+
+def test_shard_for_layer_outside_layer_shards():
+    manifest = make_manifest()
+    assert manifest.shard_for_layer(4) is None
+    # base 分片没有 layer_range，不会被当作任何层的分片返回
+    manifest.shards = [s for s in manifest.shards if s.kind != "layer"]
+    assert manifest.shard_for_layer(0) is None
+
+
+def test_valid_manifest_passes_validate():
+    make_manifest().validate()
+
+
+def test_inverted_layer_range_is_rejected():
+    manifest = make_manifest()
+    manifest.shards[2].layer_range = (3, 2)
+    with pytest.raises(ValidationError, match=r"layers_2-3 的 layer_range 颠倒: \[3, 2\]"):
+        manifest.validate()
+
+
+@pytest.mark.parametrize("index", [0, 3])
+def test_base_shard_with_layer_range_is_rejected(index):
+    manifest = make_manifest()
+    manifest.shards[index].layer_range = (0, 0)
+    shard = manifest.shards[index]
+    with pytest.raises(ValidationError, match=f"{shard.kind} 分片 {shard.id} 不应有 layer_range"):
+        manifest.validate()
+
+
+def test_layer_shard_without_layer_range_is_rejected():
+    manifest = make_manifest()
+    manifest.shards[1].layer_range = None
+    with pytest.raises(ValidationError, match="layers_0-1 缺少 layer_range"):
+        manifest.validate()
+
+
+def test_depends_on_is_embed_plus_all_lower_layer_shards():
+    manifest = make_manifest()
+    assert shard_dependencies(manifest.shards) == {
+        "layers_0-1": ["embed"],
+        "layers_2-3": ["embed", "layers_0-1"],
+    }
+    entries = {e["id"]: e for e in manifest.to_dict()["shards"]}
+    assert entries["layers_2-3"]["depends_on"] == ["embed", "layers_0-1"]
+    # 只有 layer 分片有依赖；lm_head 既没有 depends_on，也不出现在任何依赖里
+    assert "depends_on" not in entries["embed"] and "depends_on" not in entries["lm_head"]
+    assert all("lm_head" not in e.get("depends_on", []) for e in entries.values())
+
+
+def test_depends_on_follows_layer_order_not_list_order():
+    manifest = make_manifest()
+    manifest.shards.reverse()
+    assert shard_dependencies(manifest.shards)["layers_2-3"] == ["embed", "layers_0-1"]
+
+
+@pytest.mark.parametrize("count, parallelism", [(1, 1), (4, 4), (6, 6), (10, 6)])
+def test_download_hints_scale_with_shard_count(count, parallelism):
+    shards = [Shard(f"layers_{i}", "layer", f"model.onnx_data_{i}", 10 * (i + 1), _hex("a"), layer_range=(i, i))
+              for i in range(count)]
+    manifest = ModelManifest("pb-ai/test", "base", "onnxruntime-web", "int8", count, shards)
+    assert manifest.to_dict()["download_hints"] == {
+        "suggested_parallelism": parallelism,
+        "total_bytes": manifest.total_bytes(),
+    }
+
+
+def test_download_hints_for_empty_manifest():
+    manifest = make_manifest()
+    manifest.shards = []
+    assert manifest.to_dict()["download_hints"] == {"suggested_parallelism": 1, "total_bytes": 0}
+
+
+def test_written_manifest_has_download_hints(tmp_path, shard_tiny):
+    manifest, _ = shard_tiny(tmp_path)
+    data = json.loads((tmp_path / "manifest.json").read_text())
+    assert data["download_hints"] == {"suggested_parallelism": 4, "total_bytes": manifest.total_bytes()}
+
+
+def test_created_by_and_notes_round_trip():
+    manifest = make_manifest()
+    manifest.created_by = "ci@pb-ai"
+    manifest.notes = "多行\n说明"
+    data = manifest.to_dict()
+    assert data["created_by"] == "ci@pb-ai" and data["notes"] == "多行\n说明"
+    restored = ModelManifest.from_dict(json.loads(json.dumps(data)))
+    assert (restored.created_by, restored.notes) == ("ci@pb-ai", "多行\n说明")
+
+
+@pytest.mark.parametrize("value", [None, ""])
+def test_created_by_and_notes_omitted_when_unset(value):
+    manifest = make_manifest()
+    manifest.created_by = manifest.notes = value
+    data = manifest.to_dict()
+    assert "created_by" not in data and "notes" not in data
+    restored = ModelManifest.from_dict(data)
+    assert restored.created_by is None and restored.notes is None
+
+
+def test_created_by_and_notes_from_cli(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+            "--created-by", "release-bot", "--notes", "nightly")
+    data = json.loads((output / "manifest.json").read_text())
+    assert (data["created_by"], data["notes"]) == ("release-bot", "nightly")
+
+
+def test_compact_and_pretty_manifests_load_equal(tmp_path):
+    manifest = make_manifest()
+    manifest.notes = "含中文与\n换行"
+    pretty = manifest.write(tmp_path, tmp_path / "pretty.json")
+    compact = manifest.write(tmp_path, tmp_path / "compact.json", compact=True)
+
+    assert "\n" not in compact.read_text() and ": " not in compact.read_text()
+    assert compact.stat().st_size < pretty.stat().st_size
+    assert json.loads(compact.read_text()) == json.loads(pretty.read_text())
+    assert ModelManifest.from_dict(json.loads(compact.read_text())) == ModelManifest.from_dict(
+        json.loads(pretty.read_text())
+    ) == manifest
@@ -0,0 +1,74 @@
+"""--max-file-bytes: 规划阶段保证单个分片文件不超过上限。"""
+
+import pytest
+from onnx import TensorProto
+
+from src.errors import ValidationError
+from src.parser import ClassifyResult
+from src.writer import plan_shards, write_shards
+from src.selftest import build_tiny_model
+
+
+def _layers_only(*tensor_bytes_per_layer: list[int]) -> ClassifyResult:
+    """只有 layer tensor 的分类结果 (没有 base 分片)，每层的 tensor 大小由参数给出。"""
+    result = ClassifyResult()
+    for layer, sizes in enumerate(tensor_bytes_per_layer):
+        result.layers[layer] = [
+            TensorProto(name=f"w{layer}_{i}", dims=[size], data_type=TensorProto.INT8, raw_data=bytes(size))
+            for i, size in enumerate(sizes)
+        ]
+    result.max_layer = len(tensor_bytes_per_layer) - 1
+    return result
+
+
+def _ranges(plans):
+    return [plan.layer_range for plan in plans]
+
+
+def test_small_limit_forces_subdivision():
+    result = _layers_only([40], [40], [40], [40])
+    plans = plan_shards(result, layers_per_chunk=4, max_file_bytes=100)
+    assert _ranges(plans) == [(0, 1), (2, 3)]
+
+
+def test_limit_not_reached_keeps_ranges():
+    result = _layers_only([40], [40], [40], [40])
+    assert _ranges(plan_shards(result, layers_per_chunk=4, max_file_bytes=160)) == [(0, 3)]
+
+
+def test_single_tensor_over_limit_is_an_error():
+    result = _layers_only([40], [200], [40])
+    with pytest.raises(ValidationError, match="layer 1"):
+        plan_shards(result, layers_per_chunk=1, max_file_bytes=100)
+
+
+def test_alignment_padding_counts_against_the_limit():
+    # 对齐到 16 时每个 tensor 最多补 15 字节: 40 + 40 = 80 <= 100，但 55 + 55 = 110 > 100
+    result = _layers_only([40], [40])
+    assert _ranges(plan_shards(result, layers_per_chunk=2, max_file_bytes=100)) == [(0, 1)]
+    assert _ranges(plan_shards(result, layers_per_chunk=2, max_file_bytes=100, align=16)) == [(0, 0), (1, 1)]
+
+
+def test_alignment_padding_can_push_a_layer_over_the_limit():
+    result = _layers_only([45, 45])
+    plan_shards(result, layers_per_chunk=1, max_file_bytes=100)
+    with pytest.raises(ValidationError, match="layer 0"):
+        plan_shards(result, layers_per_chunk=1, max_file_bytes=100, align=16)
+
+
+def test_base_shard_over_limit_is_an_error():
+    from src.parser import classify_initializers
+    model = build_tiny_model()
+    result = classify_initializers(list(model.graph.initializer), model.graph)
+    # embed 分片: 128 + 16 字节；对齐到 48 时最多 144 + 2 * 47
+    plan_shards(result, layers_per_chunk=1, max_file_bytes=144)
+    with pytest.raises(ValidationError, match="model.onnx_data_embed"):
+        plan_shards(result, layers_per_chunk=1, max_file_bytes=144, align=48)
+
+
+def test_written_files_respect_limit(tmp_path):
+    result = _layers_only([40, 40], [40, 40], [40, 40])
+    plans = plan_shards(result, layers_per_chunk=3, max_file_bytes=200, align=32)
+    model = build_tiny_model()
+    shards = write_shards(model, plans, tmp_path, align=32)
+    assert all(shard.bytes <= 200 for shard in shards)
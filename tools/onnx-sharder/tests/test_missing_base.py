@@ -0,0 +1,39 @@
+"""缺少必需的 base 权重：默认只警告，--strict-base 时报错。"""
+
+import json
+
+import onnx
+import pytest
+
+from src.errors import ValidationError
+from src.parser import classify_initializers, missing_base_tensors
+from src.selftest import build_tiny_model
+
+
+@pytest.fixture
+def no_norm_model_path(tmp_path):
+    model = build_tiny_model()
+    norm = next(t for t in model.graph.initializer if t.name == "model.norm.weight")
+    model.graph.initializer.remove(norm)
+    path = tmp_path / "input" / "model.onnx"
+    path.parent.mkdir()
+    onnx.save(model, str(path))
+    return path
+
+
+def test_complete_model_has_no_missing_base(tiny_model):
+    assert missing_base_tensors(classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)) == []
+
+
+def test_missing_norm_is_an_error_under_strict_base(tmp_path, no_norm_model_path, run_cli):
+    output = tmp_path / "out"
+    with pytest.raises(ValidationError, match=r"model\.norm"):
+        run_cli("--input", no_norm_model_path, "--output", output, "--model-id", "t/m", "--strict-base")
+    assert not (output / "manifest.json").exists()
+
+
+def test_missing_norm_only_warns_by_default(tmp_path, no_norm_model_path, run_cli, capsys):
+    output = tmp_path / "out"
+    run_cli("--input", no_norm_model_path, "--output", output, "--model-id", "t/m")
+    assert "警告: 缺少必需的 base 权重: model.norm.*" in capsys.readouterr().err
+    assert json.loads((output / "manifest.json").read_text())["shards"]
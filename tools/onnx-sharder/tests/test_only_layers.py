@@ -0,0 +1,66 @@
+"""--only-layers: 只为选中的层生成分片，其余层写到 manifest 之外的文件。"""
+
+import json
+
+import onnx
+import pytest
+
+from src import selftest
+from src.writer.regenerate import _tensor_location
+
+
+@pytest.fixture
+def three_layer_model_path(tmp_path, monkeypatch):
+    monkeypatch.setattr(selftest, "NUM_LAYERS", 3)
+    path = tmp_path / "input" / "model.onnx"
+    path.parent.mkdir()
+    onnx.save(selftest.build_tiny_model(), str(path))
+    return path
+
+
+def _locations(output):
+    model = onnx.load(str(output / "model.onnx"), load_external_data=False)
+    return {t.name: _tensor_location(t) for t in model.graph.initializer}
+
+
+def test_non_contiguous_selection(tmp_path, three_layer_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", three_layer_model_path, "--output", output, "--model-id", "t/m", "--only-layers", "0,2")
+
+    manifest = json.loads((output / "manifest.json").read_text())
+    assert [(s["id"], s.get("layer_range")) for s in manifest["shards"]] == [
+        ("embed", None), ("layer_0", [0, 0]), ("layer_2", [2, 2]), ("lm_head", None),
+    ]
+    assert manifest["partial"] is True
+    assert manifest["included_layers"] == [0, 2]
+    assert manifest["total_layers"] == 3
+    # 第 2 层只依赖 embed 与实际存在的第 0 层分片
+    assert manifest["shards"][2]["depends_on"] == ["embed", "layer_0"]
+
+    # 未选中的第 1 层不内联在 model.onnx 中，而是写到不列入 manifest 的文件
+    locations = _locations(output)
+    assert None not in locations.values()
+    assert locations["onnx::MatMul_101"] == "model.onnx_data_layers_excluded"
+    assert locations["onnx::MatMul_100"] == "model.onnx_data_0"
+    assert locations["onnx::MatMul_102"] == "model.onnx_data_2"
+    assert (output / "model.onnx_data_layers_excluded").is_file()
+    assert "model.onnx_data_layers_excluded" not in [s["filename"] for s in manifest["shards"]]
+
+
+def test_manifest_only_keeps_partial_selection(tmp_path, three_layer_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", three_layer_model_path, "--output", output, "--model-id", "t/m", "--only-layers", "0,2")
+    original = json.loads((output / "manifest.json").read_text())
+
+    run_cli("--output", output, "--model-id", "t/m", "--manifest-only")
+    regenerated = json.loads((output / "manifest.json").read_text())
+    assert regenerated["shards"] == original["shards"]
+    assert regenerated["partial"] is True
+    assert regenerated["included_layers"] == [0, 2]
+
+
+def test_selecting_every_layer_writes_no_extra_file(tmp_path, three_layer_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", three_layer_model_path, "--output", output, "--model-id", "t/m", "--only-layers", "0,1,2")
+    assert not (output / "model.onnx_data_layers_excluded").exists()
+    assert "partial" not in json.loads((output / "manifest.json").read_text())
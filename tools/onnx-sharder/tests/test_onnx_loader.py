@@ -0,0 +1,53 @@
+"""load_onnx_model: 缺失、空、损坏的输入和被截断的 external data 都报 InputError (退出码 3)。"""
+
+import onnx
+import pytest
+
+from src.errors import InputError
+from src.parser import load_onnx_model
+
+
+def test_missing_file(tmp_path):
+    with pytest.raises(InputError, match="输入模型不存在"):
+        load_onnx_model(tmp_path / "model.onnx")
+
+
+def test_empty_file(tmp_path):
+    path = tmp_path / "model.onnx"
+    path.write_bytes(b"")
+    with pytest.raises(InputError, match="空文件"):
+        load_onnx_model(path)
+
+
+def test_garbage_input(tmp_path):
+    path = tmp_path / "model.onnx"
+    path.write_bytes(b"\xff\xfe not a protobuf \x00" * 8)
+    with pytest.raises(InputError, match="无法解析 ONNX 模型") as exc:
+        load_onnx_model(path)
+    assert exc.value.exit_code == 3
+
+
+@pytest.fixture
+def external_model(tmp_path, shard_tiny):
+    """分片输出的 model.onnx 引用 external data，可直接作为带 external data 的输入。"""
+    shard_tiny(tmp_path)
+    return tmp_path / "model.onnx"
+
+
+def test_external_data_loads(external_model):
+    model = load_onnx_model(external_model)
+    assert all(t.data_location != onnx.TensorProto.EXTERNAL for t in model.graph.initializer)
+
+
+def test_truncated_external_data(external_model):
+    data = external_model.parent / "model.onnx_data_0"
+    data.write_bytes(data.read_bytes()[:-4])
+    with pytest.raises(InputError, match="疑似被截断: model.onnx_data_0") as exc:
+        load_onnx_model(external_model)
+    assert exc.value.exit_code == 3
+
+
+def test_missing_external_data(external_model):
+    (external_model.parent / "model.onnx_data_lm_head").unlink()
+    with pytest.raises(InputError, match="external data 文件不存在"):
+        load_onnx_model(external_model)
@@ -0,0 +1,36 @@
+"""--probe: 打印模型概况，不写任何文件。"""
+
+import json
+
+
+def _snapshot(root):
+    return sorted(p.relative_to(root) for p in root.rglob("*"))
+
+
+def test_probe_reports_tiny_model_without_writing(tmp_path, tiny_model_path, run_cli, capsys):
+    before = _snapshot(tmp_path)
+    run_cli("--probe", "--input", tiny_model_path, "--output", tmp_path / "out")
+    out = capsys.readouterr().out
+
+    assert "检测到层数 (tensor): 2" in out
+    assert "config.json: 未找到" in out
+    assert "FLOAT×5" in out
+    assert "embed: model.embed_tokens.weight" in out
+    assert "norm: model.norm.weight" in out
+    assert "lm_head: onnx::MatMul_999" in out
+    assert "共享权重" not in out
+    assert _snapshot(tmp_path) == before
+
+
+def test_probe_warns_when_config_disagrees(tmp_path, tiny_model_path, run_cli, capsys):
+    config = tiny_model_path.parent / "config.json"
+    config.write_text(json.dumps({"model_type": "llama", "num_hidden_layers": 3}))
+    before = _snapshot(tmp_path)
+    run_cli("--probe", "--input", tiny_model_path)
+    captured = capsys.readouterr()
+
+    assert f"config.json: {config}" in captured.out
+    assert "model_type: llama" in captured.out
+    assert "num_hidden_layers: 3" in captured.out
+    assert "与 tensor 检测到的层数 2 不一致" in captured.err
+    assert _snapshot(tmp_path) == before
@@ -0,0 +1,52 @@
+"""--progress-json: stderr 上的 NDJSON 生命周期事件。"""
+
+import json
+
+from src import progress
+
+
+def _events(run_cli, capsys, monkeypatch, *argv):
+    # 进度开关是模块级状态，测试结束后由 monkeypatch 恢复为关闭
+    monkeypatch.setattr(progress, "_enabled", False)
+    run_cli(*argv, "--progress-json")
+    return [json.loads(line) for line in capsys.readouterr().err.splitlines() if line.startswith("{")]
+
+
+def test_event_sequence(tmp_path, tiny_model_path, run_cli, capsys, monkeypatch):
+    output = tmp_path / "out"
+    events = _events(run_cli, capsys, monkeypatch,
+                     "--input", tiny_model_path, "--output", output, "--model-id", "t/m")
+
+    shard_ids = ["embed", "layer_0", "layer_1", "lm_head"]
+    assert [e["event"] for e in events] == (
+        ["load_done", "classify_done"]
+        + ["shard_started", "shard_done"] * len(shard_ids)
+        + ["manifest_done"]
+    )
+    assert events[0] == {"event": "load_done", "initializers": 5}
+    started, done = events[2:-1:2], events[3:-1:2]
+    assert [e["id"] for e in started] == [e["id"] for e in done] == shard_ids
+    manifest = json.loads((output / "manifest.json").read_text())
+    for event, shard in zip(done, manifest["shards"]):
+        assert event["bytes"] == shard["bytes"] and event["ms"] >= 0
+    assert [e["filename"] for e in started] == [s["filename"] for s in manifest["shards"]]
+    assert events[-1] == {"event": "manifest_done", "path": str(output / "manifest.json"), "shards": 4}
+
+
+def test_no_events_without_flag(tmp_path, tiny_model_path, run_cli, capsys, monkeypatch):
+    monkeypatch.setattr(progress, "_enabled", False)
+    run_cli("--input", tiny_model_path, "--output", tmp_path / "out", "--model-id", "t/m")
+    assert not [line for line in capsys.readouterr().err.splitlines() if line.startswith("{")]
+
+
+def test_classify_done_payload(tmp_path, tiny_model_path, run_cli, capsys, monkeypatch):
+    events = _events(run_cli, capsys, monkeypatch,
+                     "--input", tiny_model_path, "--output", tmp_path / "out", "--model-id", "t/m")
+    classify_done = next(e for e in events if e["event"] == "classify_done")
+    # 8x4 embed + 2 × 4x4 层 + 4 norm + 4x8 lm_head，全部 float32
+    assert classify_done == {
+        "event": "classify_done",
+        "total_layers": 2,
+        "tensors": 5,
+        "dtypes": {"FLOAT": {"count": 5, "bytes": 4 * (32 + 2 * 16 + 4 + 32)}},
+    }
@@ -0,0 +1,39 @@
+"""--quiet: 只丢弃进度与说明输出，警告和查询结果照常输出。"""
+
+import sys
+
+from main import main, quiet_stdout
+from src.cli import parse_args
+
+
+def _run(monkeypatch, *argv):
+    monkeypatch.setattr(sys, "argv", ["main.py", *map(str, argv)])
+    args = parse_args()
+    with quiet_stdout(args):
+        main(args)
+
+
+def test_quiet_sharding_keeps_only_warnings(tmp_path, tiny_model_path, monkeypatch, capsys):
+    output = tmp_path / "out"
+    _run(monkeypatch, "--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+         "--quiet", "--warn-on-large-base", "0.5")
+
+    captured = capsys.readouterr()
+    assert captured.out == ""
+    assert "警告: model.onnx_data_embed" in captured.err
+    assert (output / "manifest.json").is_file()
+
+
+def test_quiet_verify_still_reports_results(tmp_path, shard_tiny, monkeypatch, capsys):
+    shard_tiny(tmp_path)
+    capsys.readouterr()
+    _run(monkeypatch, "--verify", "--output", tmp_path, "--quiet")
+    assert "[PASS] model.onnx_data_embed" in capsys.readouterr().out
+
+
+def test_quiet_plan_json_is_emitted(tmp_path, shard_tiny, monkeypatch, capsys):
+    shard_tiny(tmp_path / "remote")
+    capsys.readouterr()
+    _run(monkeypatch, "--plan-download", tmp_path / "remote" / "manifest.json",
+         "--output", tmp_path / "local", "--plan-json", "--quiet")
+    assert '"pending_bytes"' in capsys.readouterr().out
@@ -0,0 +1,85 @@
+"""分片层区间切分的纯函数 (plan_shards 的基础)。"""
+
+import pytest
+
+from src.errors import ValidationError
+from src.writer.shard_writer import (
+    balanced_layer_ranges, byte_limited_ranges, layer_chunk_ranges, selected_layer_ranges,
+    tensor_count_ranges,
+)
+
+
+@pytest.mark.parametrize("total_layers, per, expected", [
+    (4, 1, [(0, 0), (1, 1), (2, 2), (3, 3)]),
+    (1, 1, [(0, 0)]),
+    (1, 4, [(0, 0)]),
+    (3, 8, [(0, 2)]),
+    (4, 2, [(0, 1), (2, 3)]),
+    (5, 2, [(0, 1), (2, 3), (4, 4)]),
+    (7, 3, [(0, 2), (3, 5), (6, 6)]),
+    (0, 2, []),
+])
+def test_layer_chunk_ranges(total_layers, per, expected):
+    assert layer_chunk_ranges(total_layers, per) == expected
+
+
+@pytest.mark.parametrize("total_layers, per", [(1, 1), (5, 2), (22, 4), (3, 8)])
+def test_layer_chunk_ranges_tiles_every_layer_once(total_layers, per):
+    ranges = layer_chunk_ranges(total_layers, per)
+    layers = [layer for start, end in ranges for layer in range(start, end + 1)]
+    assert layers == list(range(total_layers))
+    assert all(end - start + 1 <= per for start, end in ranges)
+
+
+def test_layer_chunk_ranges_rejects_zero():
+    with pytest.raises(ValueError):
+        layer_chunk_ranges(4, 0)
+
+
+@pytest.mark.parametrize("total_layers, count, expected", [
+    (10, 3, [(0, 3), (4, 6), (7, 9)]),
+    (9, 3, [(0, 2), (3, 5), (6, 8)]),
+    (2, 5, [(0, 0), (1, 1)]),
+    (1, 1, [(0, 0)]),
+])
+def test_balanced_layer_ranges(total_layers, count, expected):
+    assert balanced_layer_ranges(total_layers, count) == expected
+
+
+def test_balanced_layer_ranges_rejects_zero():
+    with pytest.raises(ValueError):
+        balanced_layer_ranges(4, 0)
+
+
+@pytest.mark.parametrize("layers, per, expected", [
+    ([0, 5, 6, 7, 31], 2, [(0, 0), (5, 6), (7, 7), (31, 31)]),
+    ([3], 4, [(3, 3)]),
+    ([2, 1, 0, 1], 1, [(0, 0), (1, 1), (2, 2)]),
+    ([0, 1, 2, 3], 4, [(0, 3)]),
+])
+def test_selected_layer_ranges(layers, per, expected):
+    assert selected_layer_ranges(layers, per) == expected
+
+
+@pytest.mark.parametrize("counts, limit, expected", [
+    ([(0, 2), (1, 2), (2, 2)], 4, [(0, 1), (2, 2)]),
+    ([(0, 2), (1, 2), (2, 2)], 1, [(0, 0), (1, 1), (2, 2)]),
+    ([(0, 5), (1, 1)], 3, [(0, 0), (1, 1)]),
+    # 层号不连续处总是断开
+    ([(0, 1), (1, 1), (5, 1)], 10, [(0, 1), (5, 5)]),
+    ([], 4, []),
+])
+def test_tensor_count_ranges(counts, limit, expected):
+    assert tensor_count_ranges(counts, limit) == expected
+
+
+def test_byte_limited_ranges_subdivides():
+    layer_bytes = {0: 40, 1: 40, 2: 40, 3: 40}
+    assert byte_limited_ranges([(0, 3)], layer_bytes, 100) == [(0, 1), (2, 3)]
+    assert byte_limited_ranges([(0, 3)], layer_bytes, 160) == [(0, 3)]
+    assert byte_limited_ranges([(0, 1), (2, 3)], layer_bytes, 40) == [(0, 0), (1, 1), (2, 2), (3, 3)]
+
+
+def test_byte_limited_ranges_rejects_oversized_layer():
+    with pytest.raises(ValidationError, match="layer 1"):
+        byte_limited_ranges([(0, 2)], {0: 10, 1: 200, 2: 10}, 100)
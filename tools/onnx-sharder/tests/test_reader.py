@@ -0,0 +1,41 @@
+"""iter_tensors: 按 manifest 从分片集合逐个读回 tensor。"""
+
+import json
+
+import onnx
+from onnx import numpy_helper
+
+from src.reader import iter_tensors
+from src.selftest import build_tiny_model
+from src.writer import ModelManifest
+
+
+def _expected():
+    return {t.name: numpy_helper.to_array(t).tobytes() for t in build_tiny_model().graph.initializer}
+
+
+def test_reads_back_every_tensor(tmp_path, shard_tiny):
+    manifest, _ = shard_tiny(tmp_path)
+    streamed = {name: numpy_helper.to_array(t).tobytes() for name, t in iter_tensors(manifest, tmp_path)}
+    assert streamed == _expected()
+
+
+def test_manifest_stored_elsewhere(tmp_path, shard_tiny):
+    shards_dir = tmp_path / "shards"
+    written, _ = shard_tiny(shards_dir)
+    published = tmp_path / "registry" / "manifest.json"
+    written.write(shards_dir, published)
+
+    manifest = ModelManifest.from_dict(json.loads(published.read_text()))
+    streamed = {name: numpy_helper.to_array(t).tobytes() for name, t in iter_tensors(manifest, shards_dir)}
+    assert streamed == _expected()
+
+
+def test_partial_output_includes_inline_tensors(tmp_path, shard_tiny):
+    # layer 1 没有写出分片，仍内联在 model.onnx 中
+    manifest, _ = shard_tiny(tmp_path, only_layers=[0])
+    model = onnx.load(str(tmp_path / "model.onnx"), load_external_data=False)
+    assert any(t.data_location != onnx.TensorProto.EXTERNAL for t in model.graph.initializer)
+
+    streamed = {name: numpy_helper.to_array(t).tobytes() for name, t in iter_tensors(manifest, tmp_path)}
+    assert streamed == _expected()
@@ -0,0 +1,39 @@
+"""--emit-readme: CHUNKS.md 的分片表与 manifest 一致。"""
+
+import json
+
+from src.writer import ModelManifest, Shard
+from src.writer.readme import render_chunks_markdown
+
+
+def _table_rows(markdown):
+    rows = [line for line in markdown.splitlines() if line.startswith("| ")]
+    return [[cell.strip() for cell in row.strip("|").split("|")] for row in rows[1:]]
+
+
+def test_one_row_per_shard_with_id_size_and_digest():
+    mib = 1024 * 1024
+    manifest = ModelManifest(
+        model_id="pb-ai/test", variant="base", framework="onnxruntime-web", dtype="int8", total_layers=3,
+        shards=[
+            Shard("embed", "embed", "model.onnx_data_embed", 3 * mib, "a" * 64),
+            Shard("layers_0-1", "layer", "model.onnx_data_0", mib // 2, "b" * 64, layer_range=(0, 1)),
+            Shard("layer_2", "layer", "model.onnx_data_2", mib, "c" * 64, layer_range=(2, 2)),
+        ],
+    )
+    assert _table_rows(render_chunks_markdown(manifest)) == [
+        ["embed", "embed", "-", "3.00", "`model.onnx_data_embed`", f"`blake3:{'a' * 64}`"],
+        ["layers_0-1", "layer", "0-1", "0.50", "`model.onnx_data_0`", f"`blake3:{'b' * 64}`"],
+        ["layer_2", "layer", "2", "1.00", "`model.onnx_data_2`", f"`blake3:{'c' * 64}`"],
+    ]
+
+
+def test_emit_readme_matches_written_manifest(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", "--emit-readme")
+
+    data = json.loads((output / "manifest.json").read_text())
+    markdown = (output / "CHUNKS.md").read_text()
+    rows = _table_rows(markdown)
+    assert [(row[0], row[5]) for row in rows] == [(s["id"], f"`{s['hash']}`") for s in data["shards"]]
+    assert f"manifest_digest: `{data['manifest_digest']}`" in markdown
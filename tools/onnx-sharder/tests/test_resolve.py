@@ -0,0 +1,28 @@
+"""Shard.resolve: filename 只能是 manifest 目录下的相对路径。"""
+
+import pytest
+
+from src.errors import ValidationError
+from src.writer import Shard
+
+
+def _shard(filename):
+    return Shard("layer_0", "layer", filename, 1, "a" * 64, layer_range=(0, 0))
+
+
+@pytest.mark.parametrize("filename", [
+    "/etc/passwd",
+    "../model.onnx_data_0",
+    "shards/../../model.onnx_data_0",
+    "..\\model.onnx_data_0",
+    "C:\\shards\\model.onnx_data_0",
+    "\\\\server\\share\\model.onnx_data_0",
+])
+def test_absolute_or_escaping_filename_is_rejected(tmp_path, filename):
+    with pytest.raises(ValidationError, match="不是相对路径"):
+        _shard(filename).resolve(tmp_path)
+
+
+@pytest.mark.parametrize("filename", ["model.onnx_data_0", "shards/model.onnx_data_0", "a..b/model.onnx_data_0"])
+def test_relative_filename_resolves_against_manifest_dir(tmp_path, filename):
+    assert _shard(filename).resolve(tmp_path) == tmp_path / filename
@@ -0,0 +1,38 @@
+"""--self-test: 内置自检在正常环境中通过，检测到问题时失败。"""
+
+import pytest
+
+from src import selftest
+from src.errors import IntegrityError
+
+
+def test_self_test_passes(capsys):
+    assert selftest.run_self_test()
+    out = capsys.readouterr().out
+    assert "[FAIL]" not in out
+    assert "自检通过" in out
+
+
+def test_self_test_cli_exits_zero(run_cli):
+    run_cli("--self-test")
+
+
+def test_self_test_reports_failure(monkeypatch, capsys, run_cli):
+    # 写出的分片被损坏时自检必须失败，而不是静默通过
+    real_write_shards = selftest.write_shards
+
+    def corrupting_write_shards(model, plans, output_dir, **kwargs):
+        shards = real_write_shards(model, plans, output_dir, **kwargs)
+        path = output_dir / shards[0].filename
+        data = bytearray(path.read_bytes())
+        data[0] ^= 0xFF
+        path.write_bytes(bytes(data))
+        return shards
+
+    monkeypatch.setattr(selftest, "write_shards", corrupting_write_shards)
+    assert not selftest.run_self_test()
+    out = capsys.readouterr().out
+    assert "[FAIL] model.onnx_data_embed 大小与 hash 一致" in out
+    assert "自检失败" in out
+    with pytest.raises(IntegrityError, match="自检未通过"):
+        run_cli("--self-test")
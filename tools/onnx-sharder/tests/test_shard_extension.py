@@ -0,0 +1,46 @@
+"""--shard-extension: 分片文件名、manifest 条目与 model.onnx 的引用使用同一扩展名。"""
+
+import json
+
+import onnx
+import pytest
+
+
+def _locations(model_path):
+    model = onnx.load(str(model_path), load_external_data=False)
+    return {
+        entry.value
+        for tensor in model.graph.initializer
+        for entry in tensor.external_data
+        if entry.key == "location"
+    }
+
+
+@pytest.mark.parametrize("extension", ["bin", ".bin"])
+def test_extension_applies_to_files_and_manifest(tmp_path, tiny_model_path, run_cli, extension):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+            "--shard-extension", extension)
+
+    expected = [
+        "model.onnx_data_embed.bin", "model.onnx_data_0.bin",
+        "model.onnx_data_1.bin", "model.onnx_data_lm_head.bin",
+    ]
+    data = json.loads((output / "manifest.json").read_text())
+    assert [s["filename"] for s in data["shards"]] == expected
+    assert [s["id"] for s in data["shards"]] == ["embed", "layer_0", "layer_1", "lm_head"]
+    assert sorted(p.name for p in output.glob("model.onnx_data*")) == sorted(expected)
+    assert _locations(output / "model.onnx") == set(expected)
+
+    # 由 model.onnx 重建的 manifest 与原 manifest 一致
+    manifest_path = output / "manifest.json"
+    manifest_path.unlink()
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", "--manifest-only")
+    assert json.loads(manifest_path.read_text()) == data
+
+
+@pytest.mark.parametrize("extension", ["", ".", "a/b", "a\\b"])
+def test_invalid_extension_is_a_usage_error(tmp_path, tiny_model_path, run_cli, extension):
+    with pytest.raises(SystemExit) as exc:
+        run_cli("--input", tiny_model_path, "--output", tmp_path / "out", "--shard-extension", extension)
+    assert exc.value.code == 2
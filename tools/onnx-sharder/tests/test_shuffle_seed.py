@@ -0,0 +1,91 @@
+"""--shuffle-seed: 同一种子得到逐字节相同的分片；种子限制在 JS 可精确表示的范围内，并原样记入 manifest。"""
+
+import json
+import sys
+
+import pytest
+from onnx import TensorProto
+
+from src.cli import parse_args
+from src.errors import ValidationError
+from src.parser import ClassifyResult
+from src.selftest import build_tiny_model
+from src.writer import ModelManifest, plan_shards, write_shards
+from src.writer.manifest import MAX_SHUFFLE_SEED
+
+
+def _parse(monkeypatch, *argv):
+    monkeypatch.setattr(
+        sys, "argv", ["main.py", "--input", "m.onnx", "--output", "out", "--model-id", "t/m", *argv],
+    )
+    return parse_args()
+
+
+def test_largest_safe_seed_is_accepted(monkeypatch):
+    assert _parse(monkeypatch, "--shuffle-seed", str(2**53 - 1)).shuffle_seed == MAX_SHUFFLE_SEED
+
+
+@pytest.mark.parametrize("seed", [str(2**53), str(2**64 - 1), "-1"])
+def test_seed_outside_js_safe_range_is_a_usage_error(monkeypatch, seed):
+    with pytest.raises(SystemExit) as exc:
+        _parse(monkeypatch, "--shuffle-seed", seed)
+    assert exc.value.code == 2
+
+
+def test_validate_rejects_unsafe_seed():
+    manifest = ModelManifest(
+        model_id="pb-ai/test", variant="base", framework="onnxruntime-web", dtype="fp32",
+        total_layers=0, shards=[], shuffle_seed=2**53,
+    )
+    with pytest.raises(ValidationError, match="shuffle_seed"):
+        manifest.validate()
+
+
+def test_seed_round_trips_through_manifest(tmp_path, tiny_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m",
+            "--shuffle-seed", MAX_SHUFFLE_SEED)
+    assert json.loads((output / "manifest.json").read_text())["shuffle_seed"] == MAX_SHUFFLE_SEED
+
+
+def _result() -> ClassifyResult:
+    """embed 6 个、两层各 4 个 tensor，每个 tensor 内容不同，便于比较写出的字节。"""
+
+    def tensor(name: str, fill: int) -> TensorProto:
+        return TensorProto(name=name, dims=[16], data_type=TensorProto.UINT8, raw_data=bytes([fill]) * 16)
+
+    result = ClassifyResult()
+    result.embed = [tensor(f"model.embed_tokens.w{i}", i) for i in range(6)]
+    for layer in range(2):
+        result.layers[layer] = [tensor(f"layers.{layer}.w{i}", 16 * (layer + 1) + i) for i in range(4)]
+    result.max_layer = 1
+    return result
+
+
+def _shard_bytes(output_dir, seed):
+    plans = plan_shards(_result(), layers_per_chunk=2, shuffle_seed=seed)
+    shards = write_shards(build_tiny_model(), plans, output_dir)
+    return {shard.filename: (output_dir / shard.filename).read_bytes() for shard in shards}
+
+
+def test_same_seed_gives_identical_shard_bytes(tmp_path):
+    first = _shard_bytes(tmp_path / "a", 42)
+    assert first == _shard_bytes(tmp_path / "b", 42)
+    assert first != _shard_bytes(tmp_path / "c", None)
+
+
+def test_different_seed_gives_different_shard_bytes(tmp_path):
+    first = _shard_bytes(tmp_path / "a", 42)
+    second = _shard_bytes(tmp_path / "b", 43)
+    assert first.keys() == second.keys()
+    assert all(first[name] != second[name] for name in first)
+    # 只是顺序不同，内容相同
+    assert all(sorted(first[name]) == sorted(second[name]) for name in first)
+
+
+def test_layer_shards_shuffle_only_within_each_layer():
+    result = _result()
+    [_, layers] = plan_shards(result, layers_per_chunk=2, shuffle_seed=7)
+    names = [t.name for t in layers.tensors]
+    assert [name.split(".w")[0] for name in names] == ["layers.0"] * 4 + ["layers.1"] * 4
+    assert sorted(names) == sorted(t.name for ts in result.layers.values() for t in ts)
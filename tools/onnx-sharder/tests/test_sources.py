@@ -0,0 +1,48 @@
+"""--verify-sources: 分片前按校验和文件检查输入，源文件损坏时不写出任何分片。"""
+
+import pytest
+
+from src.errors import InputError, IntegrityError
+from src.sources import verify_sources
+from src.writer.manifest import blake3_file
+
+
+@pytest.fixture
+def sum_file(tiny_model_path):
+    path = tiny_model_path.parent / "SHA.b3"
+    path.write_text(f"# b3sum model.onnx\n{blake3_file(tiny_model_path)}  model.onnx\n")
+    return path
+
+
+def _corrupt(path):
+    data = bytearray(path.read_bytes())
+    data[-1] ^= 0xFF
+    path.write_bytes(bytes(data))
+
+
+def test_matching_sources_pass_and_sharding_proceeds(tmp_path, tiny_model_path, sum_file, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", "--verify-sources", sum_file)
+    assert (output / "manifest.json").is_file()
+
+
+def test_corrupted_source_fails_before_any_shard_is_written(tmp_path, tiny_model_path, sum_file, run_cli, capsys):
+    _corrupt(tiny_model_path)
+    output = tmp_path / "out"
+    with pytest.raises(IntegrityError, match="1 个源文件 hash 不符"):
+        run_cli("--input", tiny_model_path, "--output", output, "--model-id", "t/m", "--verify-sources", sum_file)
+    assert f"[FAIL] {tiny_model_path}" in capsys.readouterr().out
+    assert not output.exists()
+
+
+def test_missing_source_is_an_input_error(tiny_model_path, sum_file):
+    tiny_model_path.unlink()
+    with pytest.raises(InputError, match="源文件不存在"):
+        verify_sources(sum_file)
+
+
+def test_malformed_sum_line_is_an_input_error(tmp_path):
+    path = tmp_path / "SHA.b3"
+    path.write_text("deadbeef model.onnx\n")
+    with pytest.raises(InputError, match="SHA.b3:1"):
+        verify_sources(path)
@@ -0,0 +1,46 @@
+"""临时文件写入、--clean-temp 与 --fsync。"""
+
+import os
+import subprocess
+import sys
+
+from src.writer import clean_temp_files, manifest as manifest_module, shard_writer
+
+
+def _dead_pid() -> int:
+    proc = subprocess.Popen([sys.executable, "-c", "pass"])
+    proc.wait()
+    return proc.pid
+
+
+def test_clean_temp_removes_only_files_of_dead_processes(tmp_path):
+    stale = tmp_path / f".model.onnx_data_0.{_dead_pid()}.tmp"
+    own = tmp_path / f".model.onnx_data_1.{os.getpid()}.tmp"
+    unrelated = tmp_path / "model.onnx_data_2"
+    for path in (stale, own, unrelated):
+        path.write_bytes(b"x")
+
+    assert clean_temp_files(tmp_path) == [stale]
+    assert not stale.exists()
+    assert own.exists() and unrelated.exists()
+
+
+def test_no_temp_files_left_after_write(tmp_path, shard_tiny):
+    shard_tiny(tmp_path)
+    assert not [p for p in tmp_path.iterdir() if p.name.endswith(".tmp")]
+
+
+def test_fsync_flushes_directory_after_each_rename(tmp_path, shard_tiny, monkeypatch):
+    synced = []
+    monkeypatch.setattr(shard_writer, "fsync_path", synced.append)
+    monkeypatch.setattr(manifest_module, "fsync_path", synced.append)
+
+    manifest, _ = shard_tiny(tmp_path, write_kwargs={"fsync": True})
+    # 每个分片重命名后一次目录 fsync；model.onnx 同样先 fsync 临时文件，重命名后再 fsync 目录
+    assert synced.count(tmp_path) == len(manifest.shards) + 1
+    assert synced[-2] == shard_writer.temp_path(tmp_path / "model.onnx")
+    assert synced[-1] == tmp_path
+
+    synced.clear()
+    manifest.write(tmp_path, fsync=True)
+    assert synced == [tmp_path]
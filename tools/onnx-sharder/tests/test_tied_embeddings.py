@@ -0,0 +1,50 @@
+"""tie_word_embeddings: lm_head 复用 embed_tokens 权重时不生成独立的 lm_head 分片。"""
+
+import json
+
+import onnx
+import pytest
+
+from src.parser import classify_initializers, missing_base_tensors
+from src.selftest import build_tiny_model
+
+
+def build_tied_model() -> onnx.ModelProto:
+    """/lm_head/MatMul 直接引用 model.embed_tokens.weight，没有独立的 lm_head 权重。"""
+    model = build_tiny_model()
+    lm_head_weight = next(t for t in model.graph.initializer if t.name == "onnx::MatMul_999")
+    model.graph.initializer.remove(lm_head_weight)
+    lm_head = next(n for n in model.graph.node if n.name == "/lm_head/MatMul")
+    lm_head.input[1] = "model.embed_tokens.weight"
+    return model
+
+
+@pytest.fixture
+def tied_model_path(tmp_path):
+    path = tmp_path / "input" / "model.onnx"
+    path.parent.mkdir()
+    onnx.save(build_tied_model(), str(path))
+    return path
+
+
+def test_tied_lm_head_is_detected():
+    model = build_tied_model()
+    result = classify_initializers(list(model.graph.initializer), model.graph)
+    assert result.tied_lm_head
+    assert result.lm_head == []
+    assert missing_base_tensors(result) == []
+
+
+def test_untied_model_is_not_tied(tiny_model):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    assert not result.tied_lm_head
+
+
+def test_tied_model_manifest_has_no_lm_head_shard(tmp_path, tied_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", tied_model_path, "--output", output, "--model-id", "t/m", "--strict-base")
+
+    manifest = json.loads((output / "manifest.json").read_text())
+    assert manifest["tie_word_embeddings"] is True
+    assert [s["kind"] for s in manifest["shards"]] == ["embed", "layer", "layer"]
+    assert not (output / "model.onnx_data_lm_head").exists()
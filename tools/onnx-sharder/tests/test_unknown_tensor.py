@@ -0,0 +1,46 @@
+"""--fail-on-unknown-tensor: 任何 tensor 未匹配分类规则时报错并列出全部名称。"""
+
+import json
+
+import onnx
+import pytest
+from onnx import TensorProto
+
+from src.errors import ValidationError
+from src.selftest import build_tiny_model
+from src.writer.regenerate import _tensor_location
+
+
+@pytest.fixture
+def unknown_model_path(tmp_path):
+    model = build_tiny_model()
+    for name in ("rotary.inv_freq", "extra.buffer"):
+        model.graph.initializer.append(
+            TensorProto(name=name, dims=[2], data_type=TensorProto.FLOAT, raw_data=bytes(8))
+        )
+    path = tmp_path / "model.onnx"
+    onnx.save(model, str(path))
+    return path
+
+
+def test_unknown_tensor_fails_under_flag(tmp_path, unknown_model_path, run_cli):
+    output = tmp_path / "out"
+    with pytest.raises(ValidationError, match="2 个 tensor 未匹配") as exc:
+        run_cli("--input", unknown_model_path, "--output", output, "--model-id", "t/m",
+                "--fail-on-unknown-tensor")
+    assert "rotary.inv_freq" in str(exc.value) and "extra.buffer" in str(exc.value)
+    assert not output.exists()
+
+
+def test_unknown_tensor_goes_to_embed_without_flag(tmp_path, unknown_model_path, run_cli):
+    output = tmp_path / "out"
+    run_cli("--input", unknown_model_path, "--output", output, "--model-id", "t/m")
+    assert json.loads((output / "manifest.json").read_text())["shards"][0]["id"] == "embed"
+    model = onnx.load(str(output / "model.onnx"), load_external_data=False)
+    locations = {t.name: _tensor_location(t) for t in model.graph.initializer}
+    assert locations["rotary.inv_freq"] == locations["extra.buffer"] == "model.onnx_data_embed"
+
+
+def test_known_tensors_pass_under_flag(tmp_path, tiny_model_path, run_cli):
+    run_cli("--input", tiny_model_path, "--output", tmp_path / "out", "--model-id", "t/m",
+            "--fail-on-unknown-tensor")
@@ -0,0 +1,37 @@
+"""--report-unused-base-tensors: 列出没有被任何图节点引用的 base tensor。"""
+
+import onnx
+from onnx import TensorProto
+
+from src.parser import classify_initializers, unused_base_tensors
+from src.selftest import build_tiny_model
+
+
+def _model_with_unused_buffer():
+    """在微型模型上追加一个不被任何节点引用的 norm tensor (导出时残留的缓冲区)。"""
+    model = build_tiny_model()
+    model.graph.initializer.append(
+        TensorProto(name="model.norm.bias", dims=[4], data_type=TensorProto.FLOAT, raw_data=bytes(16)),
+    )
+    return model
+
+
+def test_unreferenced_base_initializer_is_reported():
+    model = _model_with_unused_buffer()
+    result = classify_initializers(list(model.graph.initializer), model.graph)
+    assert "model.norm.bias" in [t.name for t in result.norm]
+    assert unused_base_tensors(result, model.graph) == ["model.norm.bias"]
+
+
+def test_tiny_model_has_no_unused_base_tensors(tiny_model):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    assert unused_base_tensors(result, tiny_model.graph) == []
+
+
+def test_cli_report(tmp_path, run_cli, capsys):
+    path = tmp_path / "model.onnx"
+    onnx.save(_model_with_unused_buffer(), str(path))
+    run_cli("--input", path, "--output", tmp_path / "out", "--model-id", "t/m",
+            "--report-unused-base-tensors")
+    out = capsys.readouterr().out
+    assert "未被图引用的 base tensor: 1\n  model.norm.bias\n" in out
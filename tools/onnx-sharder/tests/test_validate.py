@@ -0,0 +1,84 @@
+"""--validate-only: 只检查 manifest 自身，任何不合法都以 ValidationError (退出码 4) 报告。"""
+
+import json
+
+import pytest
+
+from src.errors import ValidationError
+from src.validate import run_validate
+
+
+@pytest.fixture
+def manifest_path(tmp_path, shard_tiny):
+    shard_tiny(tmp_path)
+    return tmp_path / "manifest.json"
+
+
+def _edit(path, change):
+    data = json.loads(path.read_text())
+    change(data)
+    path.write_text(json.dumps(data))
+
+
+def _shard(data, shard_id):
+    return next(s for s in data["shards"] if s["id"] == shard_id)
+
+
+def test_written_manifest_is_valid(manifest_path):
+    run_validate(manifest_path)
+
+
+def test_layer_gap_is_rejected(manifest_path):
+    _edit(manifest_path, lambda d: d["shards"].remove(_shard(d, "layer_1")))
+    with pytest.raises(ValidationError, match="不在任何 layer 分片中"):
+        run_validate(manifest_path)
+
+
+def test_stale_manifest_digest_is_rejected(manifest_path):
+    _edit(manifest_path, lambda d: d.update(manifest_digest="blake3:" + "0" * 64))
+    with pytest.raises(ValidationError, match="manifest_digest"):
+        run_validate(manifest_path)
+
+
+def test_duplicate_filename_is_rejected(manifest_path):
+    _edit(manifest_path, lambda d: _shard(d, "layer_1").update(filename=_shard(d, "layer_0")["filename"]))
+    with pytest.raises(ValidationError, match="filename 重复"):
+        run_validate(manifest_path)
+
+
+@pytest.mark.parametrize("layer_range", [[1], [0, 1, 2], ["0", 1], 1])
+def test_malformed_layer_range_is_rejected(manifest_path, layer_range):
+    _edit(manifest_path, lambda d: _shard(d, "layer_1").update(layer_range=layer_range))
+    with pytest.raises(ValidationError) as exc:
+        run_validate(manifest_path)
+    assert exc.value.exit_code == 4
+
+
+@pytest.mark.parametrize("value", [12, None, ["blake3:" + "a" * 64]])
+def test_non_string_hash_is_rejected(manifest_path, value):
+    _edit(manifest_path, lambda d: _shard(d, "embed").update(hash=value))
+    with pytest.raises(ValidationError):
+        run_validate(manifest_path)
+
+
+def test_non_integer_total_layers_is_rejected(manifest_path):
+    _edit(manifest_path, lambda d: d.update(total_layers="3"))
+    with pytest.raises(ValidationError, match="total_layers"):
+        run_validate(manifest_path)
+
+
+@pytest.mark.parametrize(
+    "digest",
+    ["blake3:" + "a" * 63, "blake3:" + "a" * 65, "blake3:g" + "a" * 63, "blake3:" + "A" * 64],
+    ids=["too-short", "too-long", "non-hex", "uppercase"],
+)
+def test_malformed_shard_digest_is_rejected(manifest_path, digest):
+    _edit(manifest_path, lambda d: _shard(d, "layer_0").update(hash=digest))
+    with pytest.raises(ValidationError, match="layer_0 的 hash 不是 64 位 hex"):
+        run_validate(manifest_path)
+
+
+def test_missing_blake3_prefix_is_rejected(manifest_path):
+    _edit(manifest_path, lambda d: _shard(d, "embed").update(hash="a" * 64))
+    with pytest.raises(ValidationError, match="缺少 blake3: 前缀"):
+        run_validate(manifest_path)
@@ -0,0 +1,38 @@
+"""--verbose-tensors: 逐个打印 tensor 的分类结果。"""
+
+from onnx import TensorProto
+
+from src.parser import classify_initializers
+
+TINY_LINES = [
+    "    model.embed_tokens.weight -> embed",
+    "    onnx::MatMul_100 -> layer(0)",
+    "    onnx::MatMul_101 -> layer(1)",
+    "    model.norm.weight -> norm",
+    "    onnx::MatMul_999 -> lm_head",
+    "  共 5 个 tensor: embed 1, lm_head 1, norm 1, layer 2",
+]
+
+
+def _classification_lines(out):
+    return [line for line in out.splitlines() if " -> " in line or line.startswith("  共 ")]
+
+
+def test_cli_prints_one_line_per_tensor(tmp_path, tiny_model_path, run_cli, capsys):
+    run_cli("--input", tiny_model_path, "--output", tmp_path / "out", "--model-id", "t/m", "--verbose-tensors")
+    assert _classification_lines(capsys.readouterr().out) == TINY_LINES
+
+
+def test_silent_by_default(tmp_path, tiny_model_path, run_cli, capsys):
+    run_cli("--input", tiny_model_path, "--output", tmp_path / "out", "--model-id", "t/m")
+    assert _classification_lines(capsys.readouterr().out) == []
+
+
+def test_unmatched_tensor_is_labelled(tiny_model, capsys):
+    tiny_model.graph.initializer.append(
+        TensorProto(name="rotary.inv_freq", dims=[2], data_type=TensorProto.FLOAT, raw_data=bytes(8)),
+    )
+    classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph, verbose=True)
+    lines = _classification_lines(capsys.readouterr().out)
+    assert "    rotary.inv_freq -> embed (未匹配)" in lines
+    assert lines[-1] == "  共 6 个 tensor: embed 2, lm_head 1, norm 1, layer 2"
@@ -0,0 +1,115 @@
+"""--verify: 按 manifest 并发校验整个分片目录。"""
+
+import json
+
+import pytest
+
+from src.errors import InputError
+from src.verify import plan_download, run_compare, run_verify
+
+
+def _corrupt(path):
+    data = bytearray(path.read_bytes())
+    data[0] ^= 0xFF
+    path.write_bytes(bytes(data))
+
+
+@pytest.mark.parametrize("jobs", [1, 4])
+def test_all_shards_pass(tmp_path, shard_tiny, jobs):
+    shard_tiny(tmp_path)
+    assert run_verify(tmp_path, jobs=jobs)
+
+
+@pytest.mark.parametrize("jobs", [1, 4])
+def test_detects_one_corrupt_shard_among_many(tmp_path, shard_tiny, capsys, jobs):
+    manifest, _ = shard_tiny(tmp_path)
+    _corrupt(tmp_path / "model.onnx_data_1")
+
+    assert not run_verify(tmp_path, jobs=jobs)
+    lines = [line.strip() for line in capsys.readouterr().out.splitlines() if line.strip().startswith("[")]
+    # 结果按 manifest 顺序输出，与并发调度无关
+    assert lines == [
+        f"[{'FAIL' if s.filename == 'model.onnx_data_1' else 'PASS'}] {s.filename}"
+        for s in manifest.shards
+    ]
+
+
+def test_missing_shard_fails(tmp_path, shard_tiny):
+    shard_tiny(tmp_path)
+    (tmp_path / "model.onnx_data_lm_head").unlink()
+    assert not run_verify(tmp_path, jobs=2)
+
+
+@pytest.mark.parametrize("change", [
+    lambda shard: shard.update(hash=12),
+    lambda shard: shard.update(layer_digests={"first": {"offset": 0, "length": 1, "hash": "blake3:" + "0" * 64}}),
+    lambda shard: shard.pop("filename"),
+])
+def test_malformed_manifest_is_an_input_error(tmp_path, shard_tiny, change):
+    shard_tiny(tmp_path)
+    path = tmp_path / "manifest.json"
+    data = json.loads(path.read_text())
+    change(data["shards"][1])
+    path.write_text(json.dumps(data))
+
+    with pytest.raises(InputError, match="无法解析 manifest"):
+        run_verify(tmp_path, jobs=1)
+    with pytest.raises(InputError):
+        run_compare(tmp_path, tmp_path)
+    with pytest.raises(InputError):
+        plan_download(path, tmp_path)
+
+
+def _break_local_copy(local_dir):
+    """删掉 lm_head 和 layer_1、篡改 embed，layer_0 保持完好。"""
+    (local_dir / "model.onnx_data_lm_head").unlink()
+    (local_dir / "model.onnx_data_1").unlink()
+    _corrupt(local_dir / "model.onnx_data_embed")
+
+
+def test_plan_download_lists_missing_and_mismatched_in_download_order(tmp_path, shard_tiny, capsys):
+    manifest, _ = shard_tiny(tmp_path)
+    _break_local_copy(tmp_path)
+    sizes = {s.filename: s.bytes for s in manifest.shards}
+
+    plan_download(tmp_path / "manifest.json", tmp_path, jobs=2)
+    out = capsys.readouterr().out
+    entries = [line.strip().split("\t") for line in out.splitlines() if "\t" in line]
+    # embed → layer (层号升序) → lm_head；完好的 layer_0 不出现
+    assert entries == [
+        ["model.onnx_data_embed", "mismatch", str(sizes["model.onnx_data_embed"])],
+        ["model.onnx_data_1", "missing", str(sizes["model.onnx_data_1"])],
+        ["model.onnx_data_lm_head", "missing", str(sizes["model.onnx_data_lm_head"])],
+    ]
+    assert "待下载 3/4 个分片" in out
+
+
+def test_plan_download_json(tmp_path, shard_tiny, capsys):
+    manifest, _ = shard_tiny(tmp_path)
+    _break_local_copy(tmp_path)
+    shards = {s.id: s for s in manifest.shards}
+    capsys.readouterr()
+
+    plan_download(tmp_path / "manifest.json", tmp_path, as_json=True)
+    data = json.loads(capsys.readouterr().out)
+    assert [(e["id"], e["reason"]) for e in data["shards"]] == [
+        ("embed", "mismatch"), ("layer_1", "missing"), ("lm_head", "missing"),
+    ]
+    for entry in data["shards"]:
+        shard = shards[entry["id"]]
+        assert entry == {
+            "id": shard.id,
+            "filename": shard.filename,
+            "bytes": shard.bytes,
+            "hash": f"blake3:{shard.hash}",
+            "reason": entry["reason"],
+        }
+    assert data["pending_bytes"] == sum(shards[i].bytes for i in ("embed", "layer_1", "lm_head"))
+
+
+def test_plan_download_with_complete_copy_is_empty(tmp_path, shard_tiny, capsys):
+    shard_tiny(tmp_path)
+    capsys.readouterr()
+    plan_download(tmp_path / "manifest.json", tmp_path, as_json=True)
+    data = json.loads(capsys.readouterr().out)
+    assert data["shards"] == [] and data["pending_bytes"] == 0
@@ -0,0 +1,46 @@
+"""Shard.verify_file: 单个分片文件的大小与 hash 校验。"""
+
+import pytest
+
+from src.errors import DigestMismatch, IntegrityError, SizeMismatch
+from src.writer import Shard
+from src.writer.manifest import blake3_file
+
+
+@pytest.fixture
+def shard_file(tmp_path):
+    path = tmp_path / "model.onnx_data_0"
+    path.write_bytes(bytes(range(64)))
+    shard = Shard("layer_0", "layer", path.name, 64, blake3_file(path), layer_range=(0, 0))
+    return shard, path
+
+
+def test_pass(shard_file):
+    shard, path = shard_file
+    shard.verify_file(path)
+
+
+def test_size_mismatch(shard_file):
+    shard, path = shard_file
+    path.write_bytes(bytes(range(63)))
+    with pytest.raises(SizeMismatch, match="大小不符"):
+        shard.verify_file(path)
+
+
+def test_digest_mismatch(shard_file):
+    shard, path = shard_file
+    path.write_bytes(bytes(range(1, 65)))
+    with pytest.raises(DigestMismatch, match="hash 不符") as exc:
+        shard.verify_file(path)
+    assert not isinstance(exc.value, SizeMismatch)
+
+
+def test_mismatches_are_integrity_errors():
+    assert issubclass(SizeMismatch, IntegrityError)
+    assert issubclass(DigestMismatch, IntegrityError)
+
+
+def test_missing_file_is_io_error(shard_file, tmp_path):
+    shard, _ = shard_file
+    with pytest.raises(FileNotFoundError):
+        shard.verify_file(tmp_path / "missing")
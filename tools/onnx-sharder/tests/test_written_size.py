@@ -0,0 +1,62 @@
+"""写入后的分片大小兜底检查 (_check_written_size)。"""
+
+import os
+
+import pytest
+
+from src.errors import IntegrityError
+from src.parser import classify_initializers
+from src.writer import plan_shards, shard_writer, write_shards
+
+
+def _plans(tiny_model):
+    result = classify_initializers(list(tiny_model.graph.initializer), tiny_model.graph)
+    return plan_shards(result, layers_per_chunk=1)
+
+
+def test_normal_write_passes(tmp_path, tiny_model):
+    shards = write_shards(tiny_model, _plans(tiny_model), tmp_path)
+    for shard in shards:
+        assert shard.bytes == (tmp_path / shard.filename).stat().st_size
+
+
+def test_shrunk_write_is_detected(tmp_path, tiny_model, monkeypatch):
+    real_write = shard_writer.write_data_file
+
+    def lossy_write(tensors, data_path, *args, **kwargs):
+        written = real_write(tensors, data_path, *args, **kwargs)
+        os.truncate(data_path, written // 2)
+        return written
+
+    monkeypatch.setattr(shard_writer, "write_data_file", lossy_write)
+    with pytest.raises(IntegrityError, match="写入大小异常"):
+        write_shards(tiny_model, _plans(tiny_model), tmp_path)
+    # 临时文件已删除，也没有留下同名的残缺分片
+    assert list(tmp_path.iterdir()) == []
+
+
+def test_oversized_write_is_detected(tmp_path, tiny_model, monkeypatch):
+    # 模拟某个 tensor 被展开成更宽的类型 (如 BFLOAT16 → float32)：写出的字节数是规划的两倍
+    real_bytes = shard_writer._tensor_raw_bytes
+    monkeypatch.setattr(shard_writer, "_tensor_raw_bytes", lambda t: real_bytes(t) * 2)
+    with pytest.raises(IntegrityError, match="写入大小异常"):
+        write_shards(tiny_model, _plans(tiny_model), tmp_path)
+    assert list(tmp_path.iterdir()) == []
+
+
+def test_size_must_equal_planned_size(tmp_path):
+    path = tmp_path / "model.onnx_data_0"
+    path.write_bytes(b"\0" * 10)
+    shard_writer._check_written_size(path, 10, 10)
+    with pytest.raises(IntegrityError):
+        shard_writer._check_written_size(path, 10, 16)
+    with pytest.raises(IntegrityError):
+        shard_writer._check_written_size(path, 10, 8)
+
+
+@pytest.mark.parametrize("align", [1, 16, 64])
+def test_aligned_write_matches_planned_size(tmp_path, tiny_model, align):
+    plans = _plans(tiny_model)
+    planned = [shard_writer.planned_file_bytes(plan.tensors, align) for plan in plans]
+    shards = write_shards(tiny_model, plans, tmp_path, align=align)
+    assert [shard.bytes for shard in shards] == planned